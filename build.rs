@@ -1,6 +1,11 @@
-use std::error::Error as StdError;
+use std::{env, error::Error as StdError};
 
 fn main() -> Result<(), Box<dyn StdError>> {
+	// libxml2 is only needed to link LLVM
+	if env::var_os("CARGO_FEATURE_LLVM").is_none() {
+		return Ok(());
+	}
+
 	vcpkg::find_package("libxml2")?;
 	println!("cargo:rustc-link-arg=/NODEFAULTLIB:library");
 
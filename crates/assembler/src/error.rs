@@ -3,9 +3,12 @@ use std::{
 	error::Error as StdError,
 	fmt::{Debug, Display, Formatter, Result as FmtResult, Write as _},
 	io::Error as IoError,
+	path::PathBuf,
+	process::ExitStatus,
 };
 
 use frick_instructions::BrainInstructionType;
+use frick_interop::{StopReason, Trap};
 use frick_utils::Convert as _;
 use inkwell::{builder::BuilderError, support::LLVMString, values::InstructionValueError};
 
@@ -22,6 +25,15 @@ pub enum AssemblyError {
 	NoValueInRegister(usize),
 	NoLoopInfo,
 	CannotGetConstant,
+	RuntimeNotFound(PathBuf),
+	LinkerFailed(ExitStatus),
+	ArchiverFailed(ExitStatus),
+	InvalidLibraryName(String),
+	UnsupportedInLibrary(&'static str),
+	NeedsStrictSemantics(&'static str),
+	NoTapeSnapshot,
+	Stopped(StopReason),
+	Trapped(Trap),
 	Custom(String),
 }
 
@@ -69,6 +81,40 @@ impl Display for AssemblyError {
 			}
 			Self::NoLoopInfo => f.write_str("no loop info was present when expected"),
 			Self::CannotGetConstant => f.write_str("cannot create LLVM value from rust constant"),
+			Self::RuntimeNotFound(path) => {
+				f.write_str("runtime library was not found at ")?;
+				Display::fmt(&path.display(), f)
+			}
+			Self::LinkerFailed(status) => {
+				f.write_str("linker failed with ")?;
+				Display::fmt(&status, f)
+			}
+			Self::ArchiverFailed(status) => {
+				f.write_str("archiver failed with ")?;
+				Display::fmt(&status, f)
+			}
+			Self::InvalidLibraryName(name) => {
+				f.write_char('"')?;
+				f.write_str(name)?;
+				f.write_str("\" is not a valid C identifier")
+			}
+			Self::UnsupportedInLibrary(feature) => {
+				f.write_str(feature)?;
+				f.write_str(" is not supported when compiling a library function")
+			}
+			Self::NeedsStrictSemantics(feature) => {
+				f.write_str(feature)?;
+				f.write_str(" needs the program to be compiled with strict semantics")
+			}
+			Self::NoTapeSnapshot => f.write_str("program finished without taking a tape snapshot"),
+			Self::Stopped(reason) => {
+				f.write_str("program was stopped: ")?;
+				Display::fmt(&reason, f)
+			}
+			Self::Trapped(trap) => {
+				f.write_str("program trapped: ")?;
+				Display::fmt(&trap, f)
+			}
 			Self::Custom(c) => {
 				f.write_str("custom error: ")?;
 				f.write_str(c)
@@ -91,6 +137,15 @@ impl StdError for AssemblyError {
 			| Self::NoValueInRegister(..)
 			| Self::NoLoopInfo
 			| Self::CannotGetConstant
+			| Self::RuntimeNotFound(..)
+			| Self::LinkerFailed(..)
+			| Self::ArchiverFailed(..)
+			| Self::InvalidLibraryName(..)
+			| Self::UnsupportedInLibrary(..)
+			| Self::NeedsStrictSemantics(..)
+			| Self::NoTapeSnapshot
+			| Self::Stopped(..)
+			| Self::Trapped(..)
 			| Self::Custom(..) => None,
 		}
 	}
@@ -5,6 +5,7 @@ use std::{
 	ffi::{CStr, CString},
 };
 
+use frick_spec::CellWidth;
 use frick_utils::Convert as _;
 use inkwell::{
 	AddressSpace,
@@ -25,7 +26,7 @@ use inkwell::{
 	},
 	module::Module,
 	targets::TargetMachine,
-	types::{BasicType, PointerType},
+	types::{BasicType, IntType, PointerType},
 	values::{AsValueRef, IntValue, MetadataValue, PointerValue},
 };
 
@@ -48,6 +49,8 @@ where
 pub trait ContextExt<'ctx> {
 	fn default_ptr_type(&self) -> PointerType<'ctx>;
 
+	fn cell_type(&self, cell_width: CellWidth) -> IntType<'ctx>;
+
 	fn create_named_enum_attribute(&self, name: &'static str, val: u64) -> Attribute;
 
 	fn create_range_attribute(
@@ -64,6 +67,14 @@ impl<'ctx> ContextExt<'ctx> for &'ctx Context {
 		self.ptr_type(AddressSpace::default())
 	}
 
+	fn cell_type(&self, cell_width: CellWidth) -> IntType<'ctx> {
+		match cell_width {
+			CellWidth::U8 => self.i8_type(),
+			CellWidth::U16 => self.i16_type(),
+			CellWidth::U32 => self.i32_type(),
+		}
+	}
+
 	fn create_named_enum_attribute(&self, name: &'static str, val: u64) -> Attribute {
 		self.create_enum_attribute(Attribute::get_named_enum_kind_id(name), val)
 	}
@@ -92,6 +103,14 @@ impl<'ctx> ContextExt<'ctx> for ContextRef<'ctx> {
 		self.ptr_type(AddressSpace::default())
 	}
 
+	fn cell_type(&self, cell_width: CellWidth) -> IntType<'ctx> {
+		match cell_width {
+			CellWidth::U8 => self.i8_type(),
+			CellWidth::U16 => self.i16_type(),
+			CellWidth::U32 => self.i32_type(),
+		}
+	}
+
 	fn create_named_enum_attribute(&self, name: &'static str, val: u64) -> Attribute {
 		self.create_enum_attribute(Attribute::get_named_enum_kind_id(name), val)
 	}
@@ -1,17 +1,23 @@
 use std::num::NonZero;
 
-use frick_spec::{POINTER_SIZE, TAPE_SIZE};
+use frick_instructions::BrainInstructionType;
+use frick_spec::{POINTER_SIZE, TapeBoundary};
 use frick_types::{Any, BinaryOperation, Bool, Immediate, Int, Pointer, RegOrImm, Register};
 use frick_utils::Convert as _;
 use inkwell::{
 	IntPredicate,
 	attributes::AttributeLoc,
+	basic_block::BasicBlock,
 	llvm_sys::{LLVMGEPFlagInBounds, LLVMGEPFlagNUW},
-	values::{BasicMetadataValueEnum, BasicValue, LLVMTailCallKind},
+	types::BasicMetadataTypeEnum,
+	values::{BasicMetadataValueEnum, BasicValue, IntValue, LLVMTailCallKind, PointerValue},
 };
 
-use super::{AssemblyError, InnerAssembler, LoopBlocks, utils::Castable};
-use crate::{BuilderExt as _, ContextExt, IntoContext as _};
+use super::{
+	AssemblyError, InnerAssembler, LoopBlocks,
+	utils::{Castable, LibraryParams},
+};
+use crate::{BuilderExt as _, ContextExt, IntoContext as _, library};
 
 impl<'ctx> InnerAssembler<'ctx> {
 	pub(super) fn load_cell_into_register(
@@ -19,7 +25,7 @@ impl<'ctx> InnerAssembler<'ctx> {
 		pointer_reg: Register<Pointer>,
 		output_reg: Register<Int>,
 	) -> Result<(), AssemblyError> {
-		let cell_type = self.into_context().i8_type();
+		let cell_type = self.into_context().cell_type(self.spec.cell_width());
 
 		let ptr_value = self.value_at(pointer_reg)?;
 
@@ -93,27 +99,262 @@ impl<'ctx> InnerAssembler<'ctx> {
 	) -> Result<(), AssemblyError> {
 		let context = self.into_context();
 
-		let cell_type = self.into_context().i8_type();
-		let tape_type = cell_type.array_type(TAPE_SIZE as u32);
+		let cell_type = context.cell_type(self.spec.cell_width());
+		let tape_type = cell_type.array_type(self.spec.tape_size() as u32);
 		let ptr_int_type = context
 			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
 		let zero_value = ptr_int_type.const_zero();
 		let pointer_value = self.value_at(input_reg)?;
 
-		let offset_pointer = unsafe {
-			self.builder.build_gep_with_no_wrap_flags(
-				tape_type,
-				self.pointers.tape,
-				&[zero_value, pointer_value],
-				"\0",
-				LLVMGEPFlagInBounds | LLVMGEPFlagNUW,
-			)?
+		let offset_pointer = if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			let heap_tape = self
+				.builder
+				.build_load(context.default_ptr_type(), self.pointers.tape, "\0")?
+				.into_pointer_value();
+
+			unsafe {
+				self.builder.build_gep_with_no_wrap_flags(
+					cell_type,
+					heap_tape,
+					&[pointer_value],
+					"\0",
+					LLVMGEPFlagInBounds | LLVMGEPFlagNUW,
+				)?
+			}
+		} else {
+			unsafe {
+				self.builder.build_gep_with_no_wrap_flags(
+					tape_type,
+					self.pointers.tape,
+					&[zero_value, pointer_value],
+					"\0",
+					LLVMGEPFlagInBounds | LLVMGEPFlagNUW,
+				)?
+			}
 		};
 
 		self.set_value_at(output_reg, offset_pointer)
 	}
 
+	pub(super) fn check_tape_bounds(&self, input_reg: Register<Int>) -> Result<(), AssemblyError> {
+		let context = self.into_context();
+
+		let ptr_int_type = context
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
+
+		let pointer_value = self.value_at(input_reg)?;
+
+		let out_of_bounds_block =
+			context.append_basic_block(self.functions.main, "tape.out_of_bounds\0");
+		let in_bounds_block = context.append_basic_block(self.functions.main, "tape.in_bounds\0");
+
+		match self.pointers.tape_len {
+			Some(tape_len) => {
+				let grow_block = context.append_basic_block(self.functions.main, "tape.grow\0");
+				let check_len_block =
+					context.append_basic_block(self.functions.main, "tape.check_len\0");
+
+				// the pointer is unsigned, so moving left of the first cell shows up as negative
+				let is_negative = self.builder.build_int_compare(
+					IntPredicate::SLT,
+					pointer_value,
+					ptr_int_type.const_zero(),
+					"\0",
+				)?;
+
+				self.builder.build_conditional_branch(
+					is_negative,
+					out_of_bounds_block,
+					check_len_block,
+				)?;
+				self.builder.position_at_end(check_len_block);
+
+				let tape_len_value = self
+					.builder
+					.build_load(ptr_int_type, tape_len, "\0")?
+					.into_int_value();
+
+				let needs_growth = self.builder.build_int_compare(
+					IntPredicate::UGE,
+					pointer_value,
+					tape_len_value,
+					"\0",
+				)?;
+
+				self.builder
+					.build_conditional_branch(needs_growth, grow_block, in_bounds_block)?;
+				self.builder.position_at_end(grow_block);
+
+				self.builder.build_direct_call(
+					self.functions.grow_tape,
+					&[
+						self.pointers.tape.convert::<BasicMetadataValueEnum<'ctx>>(),
+						tape_len.convert::<BasicMetadataValueEnum<'ctx>>(),
+						pointer_value.convert::<BasicMetadataValueEnum<'ctx>>(),
+						ptr_int_type
+							.const_int(self.spec.cell_width().bytes() as u64, false)
+							.convert::<BasicMetadataValueEnum<'ctx>>(),
+					],
+					"\0",
+				)?;
+
+				self.builder.build_unconditional_branch(in_bounds_block)?;
+			}
+			None => {
+				let is_out_of_bounds = self.builder.build_int_compare(
+					IntPredicate::UGE,
+					pointer_value,
+					ptr_int_type.const_int(self.spec.tape_size() as u64, false),
+					"\0",
+				)?;
+
+				self.builder.build_conditional_branch(
+					is_out_of_bounds,
+					out_of_bounds_block,
+					in_bounds_block,
+				)?;
+			}
+		}
+
+		self.builder.position_at_end(out_of_bounds_block);
+
+		if self.library.is_some() {
+			// the caller owns the process, so hand the error back instead of exiting
+			self.builder.build_return(Some(
+				&context.i32_type().const_int(library::OUT_OF_BOUNDS, false),
+			))?;
+			self.builder.position_at_end(in_bounds_block);
+
+			return Ok(());
+		}
+
+		let (line, column) = self
+			.builder
+			.get_current_debug_location()
+			.map_or((0, 0), |loc| (loc.get_line(), loc.get_column()));
+
+		let i32_type = context.i32_type();
+		let source_name = self
+			.source_name
+			.unwrap_or_else(|| context.default_ptr_type().const_null());
+
+		self.builder.build_direct_call(
+			self.functions.tape_out_of_bounds,
+			&[
+				source_name.convert::<BasicMetadataValueEnum<'ctx>>(),
+				i32_type
+					.const_int(line.into(), false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+				i32_type
+					.const_int(column.into(), false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+				pointer_value.convert::<BasicMetadataValueEnum<'ctx>>(),
+			],
+			"\0",
+		)?;
+
+		self.return_after_trap()?;
+		self.builder.position_at_end(in_bounds_block);
+
+		Ok(())
+	}
+
+	// only reached when the program runs hosted, otherwise the runtime exits the process
+	fn return_after_trap(&self) -> Result<(), AssemblyError> {
+		let context = self.into_context();
+
+		let ptr_int_type = context
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
+
+		self.release_tape(ptr_int_type)?;
+
+		self.builder
+			.build_return(Some(&context.i32_type().const_int(1, false)))?;
+
+		Ok(())
+	}
+
+	pub(super) fn check_cell_overflow(
+		&self,
+		cell_reg: Register<Int>,
+		result_reg: Register<Int>,
+		op: BinaryOperation,
+	) -> Result<(), AssemblyError> {
+		let context = self.into_context();
+
+		let cell_value = self.value_at(cell_reg)?;
+		let result_value = self.value_at(result_reg)?;
+
+		// the step is never zero, so wrapping always lands on the wrong side of the old value
+		let (predicate, underflow) = match op {
+			BinaryOperation::Add => (IntPredicate::ULT, false),
+			BinaryOperation::Sub => (IntPredicate::UGT, true),
+			op => {
+				return Err(AssemblyError::NotImplemented(
+					BrainInstructionType::CheckCellOverflow {
+						cell_reg,
+						result_reg,
+						op,
+					},
+				));
+			}
+		};
+
+		let overflowed =
+			self.builder
+				.build_int_compare(predicate, result_value, cell_value, "\0")?;
+
+		let overflow_block = context.append_basic_block(self.functions.main, "cell.overflow\0");
+		let continue_block = context.append_basic_block(self.functions.main, "cell.ok\0");
+
+		self.builder
+			.build_conditional_branch(overflowed, overflow_block, continue_block)?;
+		self.builder.position_at_end(overflow_block);
+
+		if self.library.is_some() {
+			self.builder.build_return(Some(
+				&context.i32_type().const_int(library::CELL_OVERFLOW, false),
+			))?;
+			self.builder.position_at_end(continue_block);
+
+			return Ok(());
+		}
+
+		let (line, column) = self
+			.builder
+			.get_current_debug_location()
+			.map_or((0, 0), |loc| (loc.get_line(), loc.get_column()));
+
+		let i32_type = context.i32_type();
+		let source_name = self
+			.source_name
+			.unwrap_or_else(|| context.default_ptr_type().const_null());
+
+		self.builder.build_direct_call(
+			self.functions.cell_overflow,
+			&[
+				source_name.convert::<BasicMetadataValueEnum<'ctx>>(),
+				i32_type
+					.const_int(line.into(), false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+				i32_type
+					.const_int(column.into(), false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+				context
+					.bool_type()
+					.const_int(underflow.into(), false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+			],
+			"\0",
+		)?;
+
+		self.return_after_trap()?;
+		self.builder.position_at_end(continue_block);
+
+		Ok(())
+	}
+
 	pub(super) fn perform_binary_value_operation(
 		&self,
 		lhs: RegOrImm<Int>,
@@ -151,10 +392,29 @@ impl<'ctx> InnerAssembler<'ctx> {
 	pub(super) fn input_into_register(&self, reg: Register<Int>) -> Result<(), AssemblyError> {
 		let context = self.into_context();
 
-		let cell_type = context.i8_type();
-		let call_site_value = self
-			.builder
-			.build_direct_call(self.functions.getchar, &[], "\0")?;
+		let cell_type = context.cell_type(self.spec.cell_width());
+
+		// unchanged EOF is lowered to `InputIntoCell`, so any value works here
+		let eof_value = self.spec.eof_value().unwrap_or_default();
+
+		if let Some(library) = self.library {
+			let (input, is_eof) = self.call_getc(library)?;
+
+			let eof_value = cell_type.const_int(eof_value.into(), false);
+
+			let value = self.builder.build_select(is_eof, eof_value, input, "\0")?;
+
+			return self.set_value_at(reg, value);
+		}
+
+		let call_site_value = self.builder.build_direct_call(
+			self.functions.getchar,
+			&[context
+				.i32_type()
+				.const_int(eof_value.into(), false)
+				.convert::<BasicMetadataValueEnum<'ctx>>()],
+			"\0",
+		)?;
 
 		call_site_value.set_tail_call_kind(LLVMTailCallKind::LLVMTailCallKindNoTail);
 
@@ -172,19 +432,97 @@ impl<'ctx> InnerAssembler<'ctx> {
 		Ok(())
 	}
 
+	pub(super) fn input_into_cell(
+		&self,
+		pointer_reg: Register<Pointer>,
+	) -> Result<(), AssemblyError> {
+		let ptr_value = self.value_at(pointer_reg)?;
+
+		if let Some(library) = self.library {
+			let (input, is_eof) = self.call_getc(library)?;
+
+			let current_value = self
+				.builder
+				.build_load(
+					self.into_context().cell_type(self.spec.cell_width()),
+					ptr_value,
+					"\0",
+				)?
+				.into_int_value();
+
+			let value = self
+				.builder
+				.build_select(is_eof, current_value, input, "\0")?;
+
+			self.builder.build_store(ptr_value, value)?;
+
+			return Ok(());
+		}
+
+		let ptr_int_type = self
+			.into_context()
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
+
+		let call_site_value = self.builder.build_direct_call(
+			self.functions.getchar_into,
+			&[
+				ptr_value.convert::<BasicMetadataValueEnum<'ctx>>(),
+				ptr_int_type
+					.const_int(self.spec.cell_width().bytes() as u64, false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+			],
+			"\0",
+		)?;
+
+		call_site_value.set_tail_call_kind(LLVMTailCallKind::LLVMTailCallKindNoTail);
+
+		Ok(())
+	}
+
 	pub(super) fn output_from_register(&self, reg: Register<Int>) -> Result<(), AssemblyError> {
 		let context = self.into_context();
 
 		let register_value = self.value_at(reg)?;
 
+		// only the low byte of a cell is ever written out
+		let register_value =
+			self.builder
+				.build_int_truncate_or_bit_cast(register_value, context.i8_type(), "\0")?;
+
+		let zeroext_attr = context.create_named_enum_attribute("zeroext", 0);
+
+		if let Some(library) = self.library {
+			let putc_type = context.void_type().fn_type(
+				&[
+					context.i8_type().convert::<BasicMetadataTypeEnum<'ctx>>(),
+					context
+						.default_ptr_type()
+						.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				],
+				false,
+			);
+
+			let call_site_value = self.builder.build_indirect_call(
+				putc_type,
+				library.putc,
+				&[
+					register_value.convert::<BasicMetadataValueEnum<'ctx>>(),
+					library.ctx.convert::<BasicMetadataValueEnum<'ctx>>(),
+				],
+				"\0",
+			)?;
+
+			call_site_value.add_attribute(AttributeLoc::Param(0), zeroext_attr);
+
+			return Ok(());
+		}
+
 		let call_site_value = self.builder.build_direct_call(
 			self.functions.putchar,
 			&[register_value.convert::<BasicMetadataValueEnum<'ctx>>()],
 			"\0",
 		)?;
 
-		let zeroext_attr = context.create_named_enum_attribute("zeroext", 0);
-
 		call_site_value.add_attribute(AttributeLoc::Param(0), zeroext_attr);
 		call_site_value.set_tail_call_kind(LLVMTailCallKind::LLVMTailCallKindTail);
 
@@ -254,11 +592,120 @@ impl<'ctx> InnerAssembler<'ctx> {
 	pub(super) fn jump_to_header(&self) -> Result<(), AssemblyError> {
 		let loop_info = self.last_loop_info()?;
 
+		if let (Some(fuel), Some(exit_block)) = (self.pointers.fuel, self.exit_block) {
+			self.burn_fuel(fuel, exit_block)?;
+		}
+
 		self.builder.build_unconditional_branch(loop_info.header)?;
 
 		Ok(())
 	}
 
+	fn burn_fuel(
+		&self,
+		fuel: PointerValue<'ctx>,
+		exit_block: BasicBlock<'ctx>,
+	) -> Result<(), AssemblyError> {
+		let context = self.into_context();
+		let i64_type = context.i64_type();
+
+		let current_block = self.builder.get_insert_block().unwrap();
+		let refuel_block = context.append_basic_block(self.functions.main, "fuel.refuel\0");
+		let burn_block = context.append_basic_block(self.functions.main, "fuel.burn\0");
+
+		let fuel_value = self
+			.builder
+			.build_load(i64_type, fuel, "\0")?
+			.into_int_value();
+
+		let is_empty = self.builder.build_int_compare(
+			IntPredicate::EQ,
+			fuel_value,
+			i64_type.const_zero(),
+			"\0",
+		)?;
+
+		self.builder
+			.build_conditional_branch(is_empty, refuel_block, burn_block)?;
+
+		self.builder.position_at_end(refuel_block);
+
+		let refueled = self
+			.builder
+			.build_direct_call(self.functions.refuel, &[], "\0")?
+			.try_as_basic_value()
+			.unwrap_basic()
+			.into_int_value();
+
+		let is_stopped = self.builder.build_int_compare(
+			IntPredicate::EQ,
+			refueled,
+			i64_type.const_zero(),
+			"\0",
+		)?;
+
+		self.builder
+			.build_conditional_branch(is_stopped, exit_block, burn_block)?;
+
+		self.builder.position_at_end(burn_block);
+
+		let available = self.builder.build_phi(i64_type, "\0")?;
+		available.add_incoming(&[(&fuel_value, current_block), (&refueled, refuel_block)]);
+
+		let remaining = self.builder.build_int_sub(
+			available.as_basic_value().into_int_value(),
+			i64_type.const_int(1, false),
+			"\0",
+		)?;
+
+		self.builder.build_store(fuel, remaining)?;
+
+		Ok(())
+	}
+
+	/// Reads a byte through the library's callback, returning it alongside whether input ran out.
+	fn call_getc(
+		&self,
+		library: LibraryParams<'ctx>,
+	) -> Result<(IntValue<'ctx>, IntValue<'ctx>), AssemblyError> {
+		let context = self.into_context();
+
+		let i32_type = context.i32_type();
+		let getc_type = i32_type.fn_type(
+			&[context
+				.default_ptr_type()
+				.convert::<BasicMetadataTypeEnum<'ctx>>()],
+			false,
+		);
+
+		let input = self
+			.builder
+			.build_indirect_call(
+				getc_type,
+				library.getc,
+				&[library.ctx.convert::<BasicMetadataValueEnum<'ctx>>()],
+				"\0",
+			)?
+			.try_as_basic_value()
+			.unwrap_basic()
+			.into_int_value();
+
+		let is_eof = self.builder.build_int_compare(
+			IntPredicate::SLT,
+			input,
+			i32_type.const_zero(),
+			"\0",
+		)?;
+
+		let input = self.builder.build_int_truncate_or_bit_cast(
+			input,
+			context.cell_type(self.spec.cell_width()),
+			"\0",
+		)?;
+
+		Ok((input, is_eof))
+	}
+
 	fn resolve_value<T: Castable<'ctx>>(
 		&self,
 		reg_or_imm: RegOrImm<T>,
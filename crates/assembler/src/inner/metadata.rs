@@ -1,6 +1,6 @@
 use std::num::NonZero;
 
-use frick_spec::{POINTER_SIZE, TAPE_SIZE};
+use frick_spec::{POINTER_SIZE, TapeBoundary};
 use frick_utils::Convert as _;
 use inkwell::values::{BasicMetadataValueEnum, InstructionOpcode, InstructionValue};
 
@@ -12,7 +12,9 @@ impl<'ctx> InnerAssembler<'ctx> {
 		&self,
 		instr: InstructionValue<'ctx>,
 	) -> Result<(), AssemblyError> {
-		if !matches!(instr.get_opcode(), InstructionOpcode::Load) {
+		if !matches!(instr.get_opcode(), InstructionOpcode::Load)
+			|| matches!(self.spec.boundary(), TapeBoundary::Grow)
+		{
 			return Ok(());
 		}
 
@@ -22,7 +24,7 @@ impl<'ctx> InnerAssembler<'ctx> {
 			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
 		let ptr_int_range_min = ptr_int_type.const_zero();
-		let ptr_int_range_max = ptr_int_type.const_int(TAPE_SIZE as u64, false);
+		let ptr_int_range_max = ptr_int_type.const_int(self.spec.tape_size() as u64, false);
 
 		let range_metadata_node = context.metadata_node(&[
 			ptr_int_range_min.convert::<BasicMetadataValueEnum<'ctx>>(),
@@ -2,11 +2,13 @@ mod instr;
 mod metadata;
 mod utils;
 
-use std::{cell::RefCell, fs, path::Path};
+use std::{cell::RefCell, env, num::NonZero, path::Path};
 
 use frick_instructions::{BrainInstruction, BrainInstructionType};
+use frick_spec::{POINTER_SIZE, Spec, TapeBoundary};
 use frick_utils::Convert as _;
 use inkwell::{
+	IntPredicate,
 	basic_block::BasicBlock,
 	builder::Builder,
 	context::{AsContextRef, Context},
@@ -14,20 +16,27 @@ use inkwell::{
 	llvm_sys::prelude::LLVMContextRef,
 	module::{FlagBehavior, Module},
 	targets::{TargetMachine, TargetTriple},
-	values::{BasicMetadataValueEnum, BasicValueEnum},
+	types::IntType,
+	values::{BasicMetadataValueEnum, BasicValueEnum, PointerValue},
 };
 use rustc_hash::FxHashMap;
 
 pub use self::utils::AssemblerFunctions;
-use self::utils::{AssemblerDebugBuilder, AssemblerPointers};
-use super::{AssemblyError, IntoContext as _, ModuleExt as _};
+use self::utils::{AssemblerDebugBuilder, AssemblerPointers, LibraryParams};
+use super::{AssemblyError, ContextExt as _, IntoContext as _, ModuleExt as _, library};
 
 pub struct InnerAssembler<'ctx> {
-	file_data: String,
+	file_data: &'ctx str,
+	spec: Spec,
+	source_name: Option<PointerValue<'ctx>>,
 	module: Module<'ctx>,
 	builder: Builder<'ctx>,
 	functions: AssemblerFunctions<'ctx>,
 	pointers: AssemblerPointers<'ctx>,
+	/// Present when compiling into a library function rather than `main`.
+	library: Option<LibraryParams<'ctx>>,
+	/// Where metered programs go once stopped, alongside the end of the program.
+	exit_block: Option<BasicBlock<'ctx>>,
 	target_machine: TargetMachine,
 	debug_builder: AssemblerDebugBuilder<'ctx>,
 	registers: RefCell<FxHashMap<usize, BasicValueEnum<'ctx>>>,
@@ -42,9 +51,14 @@ impl<'ctx> InnerAssembler<'ctx> {
 		cpu_name: &str,
 		cpu_features: &str,
 		file_path: &Path,
+		file_data: &'ctx str,
+		spec: Spec,
+		library_name: Option<&str>,
 	) -> Result<Self, AssemblyError> {
 		let module = context.create_module("frick\0");
-		let functions = AssemblerFunctions::new(context, &module, cpu_name, cpu_features)?;
+		let functions =
+			AssemblerFunctions::new(context, &module, cpu_name, cpu_features, library_name)?;
+		let library = library_name.and_then(|_| LibraryParams::new(functions.main));
 		let builder = context.create_builder();
 
 		let target_data = target_machine.get_target_data();
@@ -57,14 +71,53 @@ impl<'ctx> InnerAssembler<'ctx> {
 		let basic_block = context.append_basic_block(functions.main, "entry\0");
 		builder.position_at_end(basic_block);
 
-		let pointers = AssemblerPointers::new(&module, &builder)?;
+		let pointers = AssemblerPointers::new(&module, &builder, spec, library)?;
 
-		pointers.setup(&builder, functions)?;
+		pointers.setup(&builder, functions, spec, library)?;
+
+		let ptr_int_type = context
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
 		let start_block = context.append_basic_block(functions.main, "start\0");
-		builder.build_unconditional_branch(start_block)?;
+
+		match library {
+			Some(library) => {
+				let too_short_block =
+					context.append_basic_block(functions.main, "tape.too_short\0");
+
+				// wrapping and bounds checks are both against the tape size
+				let is_long_enough = builder.build_int_compare(
+					IntPredicate::UGE,
+					library.len,
+					ptr_int_type.const_int(spec.tape_size() as u64, false),
+					"\0",
+				)?;
+
+				builder.build_conditional_branch(is_long_enough, start_block, too_short_block)?;
+				builder.position_at_end(too_short_block);
+				builder.build_return(Some(
+					&context.i32_type().const_int(library::TAPE_TOO_SHORT, false),
+				))?;
+			}
+			None => {
+				builder.build_direct_call(
+					functions.set_output_threshold,
+					&[ptr_int_type
+						.const_int(spec.output_buffer_size() as u64, false)
+						.convert::<BasicMetadataValueEnum<'ctx>>()],
+					"\0",
+				)?;
+
+				builder.build_unconditional_branch(start_block)?;
+			}
+		}
+
 		builder.position_at_end(start_block);
 
+		let exit_block = pointers
+			.fuel
+			.map(|_| context.append_basic_block(functions.main, "exit\0"));
+
 		let debug_metadata_version = {
 			let i32_type = context.i32_type();
 
@@ -81,23 +134,23 @@ impl<'ctx> InnerAssembler<'ctx> {
 		);
 
 		let (file_name, directory) = {
-			assert!(file_path.is_file());
-
 			let file_name = file_path
 				.file_name()
 				.map(|s| s.to_string_lossy().into_owned())
 				.unwrap_or_default();
 
+			// synthetic sources like `<stdin>` have no parent, so fall back to the working directory
 			let directory = file_path
 				.parent()
-				.and_then(|s| s.canonicalize().ok())
+				.filter(|s| !s.as_os_str().is_empty())
+				.map_or_else(env::current_dir, Path::canonicalize)
 				.map(|s| s.to_string_lossy().into_owned())
 				.unwrap_or_default();
 
 			(file_name, directory)
 		};
 
-		let debug_builder = AssemblerDebugBuilder::new(&module, &file_name, &directory)?;
+		let debug_builder = AssemblerDebugBuilder::new(&module, &file_name, &directory, spec)?;
 
 		debug_builder.declare_subprograms(functions)?;
 
@@ -116,14 +169,27 @@ impl<'ctx> InnerAssembler<'ctx> {
 		builder.set_current_debug_location(debug_loc);
 		module.set_source_file_name(&file_name);
 
-		let file_data = fs::read_to_string(file_path)?;
+		// only needed for out of bounds and overflow messages, so keep it out of programs that can't
+		// fail either way
+		let source_name = match spec.boundary() {
+			TapeBoundary::Wrap if !spec.overflow().is_trapping() => None,
+			_ => Some(
+				builder
+					.build_global_string_ptr(&file_path.display().to_string(), "source_name\0")?
+					.as_pointer_value(),
+			),
+		};
 
 		Ok(Self {
 			file_data,
+			spec,
+			source_name,
 			module,
 			builder,
 			functions,
 			pointers,
+			library,
+			exit_block,
 			target_machine,
 			debug_builder,
 			registers: RefCell::default(),
@@ -147,12 +213,31 @@ impl<'ctx> InnerAssembler<'ctx> {
 
 		self.builder.unset_current_debug_location();
 
+		if let Some(exit_block) = self.exit_block {
+			if let Some(last_block) = self
+				.functions
+				.main
+				.get_last_basic_block()
+				.filter(|last_block| *last_block != exit_block)
+			{
+				exit_block
+					.move_after(last_block)
+					.map_err(|()| AssemblyError::Custom("unable to move exit block".to_owned()))?;
+			}
+
+			self.builder.build_unconditional_branch(exit_block)?;
+			self.builder.position_at_end(exit_block);
+		}
+
+		let ptr_int_type = self
+			.into_context()
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
+
+		if self.library.is_none() {
+			self.release_tape(ptr_int_type)?;
+		}
+
 		tracing::debug!("ending lifetimes in exit block");
-		self.builder.build_call(
-			self.functions.lifetime.end,
-			&[self.pointers.tape.convert::<BasicMetadataValueEnum<'ctx>>()],
-			"\0",
-		)?;
 		self.builder.build_call(
 			self.functions.lifetime.end,
 			&[self
@@ -162,16 +247,86 @@ impl<'ctx> InnerAssembler<'ctx> {
 			"\0",
 		)?;
 
-		self.builder.build_return(None)?;
+		let exit_code = match self.exit_block {
+			Some(..) => self
+				.builder
+				.build_direct_call(self.functions.finish_meter, &[], "\0")?
+				.try_as_basic_value()
+				.unwrap_basic()
+				.into_int_value(),
+			None => self.into_context().i32_type().const_zero(),
+		};
+
+		self.builder.build_return(Some(&exit_code))?;
 
 		self.debug_builder.di_builder.finalize();
 
 		Ok(self.into_parts())
 	}
 
+	/// Hands the tape to the snapshot hook and releases everything the runtime set up for it.
+	fn release_tape(&self, ptr_int_type: IntType<'ctx>) -> Result<(), AssemblyError> {
+		tracing::debug!("handing the tape to the snapshot hook");
+		let (tape, tape_len) = match self.pointers.tape_len {
+			Some(tape_len) => (
+				self.builder
+					.build_load(
+						self.into_context().default_ptr_type(),
+						self.pointers.tape,
+						"\0",
+					)?
+					.into_pointer_value(),
+				self.builder
+					.build_load(ptr_int_type, tape_len, "\0")?
+					.into_int_value(),
+			),
+			None => (
+				self.pointers.tape,
+				ptr_int_type.const_int(self.spec.tape_size() as u64, false),
+			),
+		};
+		let pointer = self
+			.builder
+			.build_load(ptr_int_type, self.pointers.pointer, "\0")?;
+
+		self.builder.build_direct_call(
+			self.functions.snapshot_tape,
+			&[
+				tape.convert::<BasicMetadataValueEnum<'ctx>>(),
+				tape_len.convert::<BasicMetadataValueEnum<'ctx>>(),
+				pointer.convert::<BasicMetadataValueEnum<'ctx>>(),
+				ptr_int_type
+					.const_int(self.spec.cell_width().bytes() as u64, false)
+					.convert::<BasicMetadataValueEnum<'ctx>>(),
+			],
+			"\0",
+		)?;
+
+		if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			tracing::debug!("freeing heap tape");
+			self.builder.build_direct_call(
+				self.functions.free,
+				&[tape.convert::<BasicMetadataValueEnum<'ctx>>()],
+				"\0",
+			)?;
+		}
+
+		tracing::debug!("flushing buffered output");
+		self.builder
+			.build_direct_call(self.functions.flush, &[], "\0")?;
+
+		self.builder.build_call(
+			self.functions.lifetime.end,
+			&[self.pointers.tape.convert::<BasicMetadataValueEnum<'ctx>>()],
+			"\0",
+		)?;
+
+		Ok(())
+	}
+
 	#[allow(clippy::never_loop)]
 	fn instrs(&self, instrs: &[BrainInstruction]) -> Result<(), AssemblyError> {
-		let line_positions = line_numbers::LinePositions::from(self.file_data.as_str());
+		let line_positions = line_numbers::LinePositions::from(self.file_data);
 
 		for i in instrs {
 			let i_range = i.span();
@@ -217,6 +372,14 @@ impl<'ctx> InnerAssembler<'ctx> {
 				tape_pointer_reg,
 				output_reg,
 			} => self.calculate_tape_offset(tape_pointer_reg, output_reg)?,
+			BrainInstructionType::CheckTapeBounds { tape_pointer_reg } => {
+				self.check_tape_bounds(tape_pointer_reg)?;
+			}
+			BrainInstructionType::CheckCellOverflow {
+				cell_reg,
+				result_reg,
+				op,
+			} => self.check_cell_overflow(cell_reg, result_reg, op)?,
 			BrainInstructionType::PerformBinaryValueOperation {
 				lhs,
 				rhs,
@@ -230,6 +393,9 @@ impl<'ctx> InnerAssembler<'ctx> {
 			BrainInstructionType::InputIntoRegister { output_reg } => {
 				self.input_into_register(output_reg)?;
 			}
+			BrainInstructionType::InputIntoCell { pointer_reg } => {
+				self.input_into_cell(pointer_reg)?;
+			}
 			BrainInstructionType::OutputFromRegister { input_reg } => {
 				self.output_from_register(input_reg)?;
 			}
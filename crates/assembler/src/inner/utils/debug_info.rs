@@ -4,8 +4,9 @@ use std::{
 	ops::{Deref, DerefMut},
 };
 
-use frick_spec::TAPE_SIZE;
+use frick_spec::{Spec, TapeBoundary};
 use inkwell::{
+	AddressSpace,
 	context::ContextRef,
 	debug_info::{
 		AsDIScope as _, DICompileUnit, DIFlagsConstants as _, DILexicalBlock, DILocalVariable,
@@ -30,6 +31,7 @@ impl<'ctx> AssemblerDebugBuilder<'ctx> {
 		module: &Module<'ctx>,
 		file_name: &str,
 		directory: &str,
+		spec: Spec,
 	) -> Result<Self, AssemblyError> {
 		let (di_builder, compile_unit) = module.create_debug_info_builder(
 			true,
@@ -49,8 +51,16 @@ impl<'ctx> AssemblerDebugBuilder<'ctx> {
 			"",
 		);
 
-		let main_subroutine_type =
-			di_builder.create_subroutine_type(compile_unit.get_file(), None, &[], i32::ZERO);
+		let i32_type = di_builder
+			.create_basic_type("i32", mem::size_of::<i32>() as u64 * 8, 5, i32::ZERO)?
+			.as_type();
+
+		let main_subroutine_type = di_builder.create_subroutine_type(
+			compile_unit.get_file(),
+			Some(i32_type),
+			&[],
+			i32::ZERO,
+		);
 
 		let main_subprogram = di_builder.create_function(
 			compile_unit.as_debug_info_scope(),
@@ -66,7 +76,8 @@ impl<'ctx> AssemblerDebugBuilder<'ctx> {
 			true,
 		);
 
-		let variables = AssemblerDebugVariables::new(&di_builder, compile_unit, main_subprogram)?;
+		let variables =
+			AssemblerDebugVariables::new(&di_builder, compile_unit, main_subprogram, spec)?;
 
 		Ok(Self {
 			di_builder,
@@ -113,10 +124,14 @@ impl<'ctx> AssemblerDebugBuilder<'ctx> {
 
 		functions.putchar.set_subprogram(putchar_subprogram);
 
+		let u32_type = self
+			.create_basic_type("u32", mem::size_of::<u32>() as u64 * 8, 7, i32::ZERO)?
+			.as_type();
+
 		let getchar_subroutine_type = self.create_subroutine_type(
 			self.compile_unit.get_file(),
-			Some(u8_type),
-			&[],
+			Some(u32_type),
+			&[u32_type],
 			i32::ZERO,
 		);
 
@@ -201,28 +216,53 @@ impl<'ctx> AssemblerDebugVariables<'ctx> {
 		debug_builder: &DebugInfoBuilder<'ctx>,
 		compile_unit: DICompileUnit<'ctx>,
 		main_subprogram: DISubprogram<'ctx>,
+		spec: Spec,
 	) -> Result<Self, AssemblyError> {
-		let u8_type = debug_builder
-			.create_basic_type("u8", mem::size_of::<u8>() as u64 * 8, 7, i32::ZERO)?
-			.as_type();
-
-		let tape_align_in_bits = mem::align_of::<[u8; TAPE_SIZE]>() as u32 * 8;
+		let cell_width = spec.cell_width();
+		let cell_bits = u64::from(cell_width.bits());
 
-		let tape_array_type = debug_builder
-			.create_array_type(
-				u8_type,
-				mem::size_of::<[u8; TAPE_SIZE]>() as u64 * 8,
-				tape_align_in_bits,
-				&[0..(TAPE_SIZE as i64)],
-			)
+		let cell_type = debug_builder
+			.create_basic_type(&format!("u{cell_bits}"), cell_bits, 7, i32::ZERO)?
 			.as_type();
 
+		let (tape_type, tape_align_in_bits) = match spec.boundary() {
+			TapeBoundary::Grow => {
+				let tape_align_in_bits = mem::align_of::<*mut u8>() as u32 * 8;
+
+				let tape_pointer_type = debug_builder
+					.create_pointer_type(
+						&format!("*mut u{cell_bits}"),
+						cell_type,
+						mem::size_of::<*mut u8>() as u64 * 8,
+						tape_align_in_bits,
+						AddressSpace::default(),
+					)
+					.as_type();
+
+				(tape_pointer_type, tape_align_in_bits)
+			}
+			TapeBoundary::Wrap | TapeBoundary::Abort => {
+				let tape_align_in_bits = cell_width.bits();
+
+				let tape_array_type = debug_builder
+					.create_array_type(
+						cell_type,
+						cell_bits * spec.tape_size() as u64,
+						tape_align_in_bits,
+						&[0..(spec.tape_size() as i64)],
+					)
+					.as_type();
+
+				(tape_array_type, tape_align_in_bits)
+			}
+		};
+
 		let tape = debug_builder.create_auto_variable(
 			main_subprogram.as_debug_info_scope(),
 			"tape",
 			compile_unit.get_file(),
 			0,
-			tape_array_type,
+			tape_type,
 			false,
 			i32::ZERO,
 			tape_align_in_bits,
@@ -1,3 +1,6 @@
+use std::num::NonZero;
+
+use frick_spec::POINTER_SIZE;
 use frick_utils::Convert as _;
 use inkwell::{
 	attributes::{Attribute, AttributeLoc},
@@ -14,7 +17,19 @@ use crate::{AssemblyError, ContextExt as _, IntoContext as _};
 #[derive(Debug, Clone, Copy)]
 pub struct AssemblerFunctions<'ctx> {
 	pub getchar: FunctionValue<'ctx>,
+	pub getchar_into: FunctionValue<'ctx>,
 	pub putchar: FunctionValue<'ctx>,
+	pub flush: FunctionValue<'ctx>,
+	pub set_output_threshold: FunctionValue<'ctx>,
+	pub alloc: FunctionValue<'ctx>,
+	pub free: FunctionValue<'ctx>,
+	pub grow_tape: FunctionValue<'ctx>,
+	pub tape_out_of_bounds: FunctionValue<'ctx>,
+	pub cell_overflow: FunctionValue<'ctx>,
+	pub snapshot_tape: FunctionValue<'ctx>,
+	pub start_meter: FunctionValue<'ctx>,
+	pub refuel: FunctionValue<'ctx>,
+	pub finish_meter: FunctionValue<'ctx>,
 	pub main: FunctionValue<'ctx>,
 	pub lifetime: IntrinsicFunctionSet<'ctx>,
 }
@@ -25,20 +40,160 @@ impl<'ctx> AssemblerFunctions<'ctx> {
 		module: &Module<'ctx>,
 		cpu_name: &str,
 		cpu_features: &str,
+		library_name: Option<&str>,
 	) -> Result<Self, AssemblyError> {
 		let void_type = context.void_type();
 		let i8_type = context.i8_type();
+		let i32_type = context.i32_type();
+		let i64_type = context.i64_type();
 		let ptr_type = context.default_ptr_type();
+		let ptr_int_type = context
+			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
-		let getchar_ty = i8_type.fn_type(&[], false);
+		// input is zero extended to fit any cell width, alongside the value to use on EOF
+		let getchar_ty =
+			i32_type.fn_type(&[i32_type.convert::<BasicMetadataTypeEnum<'ctx>>()], false);
 		let getchar = module.add_function("rust_getchar", getchar_ty, Some(Linkage::External));
 
+		let getchar_into_ty = void_type.fn_type(
+			&[
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let getchar_into = module.add_function(
+			"rust_getchar_into",
+			getchar_into_ty,
+			Some(Linkage::External),
+		);
+
 		let putchar_ty =
 			void_type.fn_type(&[i8_type.convert::<BasicMetadataTypeEnum<'ctx>>()], false);
 		let putchar = module.add_function("rust_putchar", putchar_ty, Some(Linkage::External));
 
-		let main_ty = void_type.fn_type(&[], false);
-		let main = module.add_function("main", main_ty, None);
+		let flush_ty = void_type.fn_type(&[], false);
+		let flush = module.add_function("rust_flush", flush_ty, Some(Linkage::External));
+
+		let set_output_threshold_ty = void_type.fn_type(
+			&[ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>()],
+			false,
+		);
+		let set_output_threshold = module.add_function(
+			"rust_set_output_threshold",
+			set_output_threshold_ty,
+			Some(Linkage::External),
+		);
+
+		let alloc_ty = ptr_type.fn_type(
+			&[ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>()],
+			false,
+		);
+		let alloc = module.add_function("rust_alloc", alloc_ty, Some(Linkage::External));
+
+		let free_ty =
+			void_type.fn_type(&[ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>()], false);
+		let free = module.add_function("rust_free", free_ty, Some(Linkage::External));
+
+		let grow_tape_ty = void_type.fn_type(
+			&[
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let grow_tape =
+			module.add_function("rust_grow_tape", grow_tape_ty, Some(Linkage::External));
+
+		let tape_out_of_bounds_ty = void_type.fn_type(
+			&[
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				i32_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				i32_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let tape_out_of_bounds = module.add_function(
+			"rust_tape_out_of_bounds",
+			tape_out_of_bounds_ty,
+			Some(Linkage::External),
+		);
+
+		let cell_overflow_ty = void_type.fn_type(
+			&[
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				i32_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				i32_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				context.bool_type().convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let cell_overflow = module.add_function(
+			"rust_cell_overflow",
+			cell_overflow_ty,
+			Some(Linkage::External),
+		);
+
+		let snapshot_tape_ty = void_type.fn_type(
+			&[
+				ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let snapshot_tape = module.add_function(
+			"rust_snapshot_tape",
+			snapshot_tape_ty,
+			Some(Linkage::External),
+		);
+
+		let start_meter_ty = void_type.fn_type(
+			&[
+				i64_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+				i64_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+			],
+			false,
+		);
+		let start_meter =
+			module.add_function("rust_start_meter", start_meter_ty, Some(Linkage::External));
+
+		let refuel_ty = i64_type.fn_type(&[], false);
+		let refuel = module.add_function("rust_refuel", refuel_ty, Some(Linkage::External));
+
+		let finish_meter_ty = i32_type.fn_type(&[], false);
+		let finish_meter = module.add_function(
+			"rust_finish_meter",
+			finish_meter_ty,
+			Some(Linkage::External),
+		);
+
+		let main = match library_name {
+			// matches `LibraryParams`, the tape is caller owned and I/O goes through callbacks
+			Some(library_name) => {
+				let library_ty = i32_type.fn_type(
+					&[
+						ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+						ptr_int_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+						ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+						ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+						ptr_type.convert::<BasicMetadataTypeEnum<'ctx>>(),
+					],
+					false,
+				);
+
+				module.add_function(library_name, library_ty, Some(Linkage::External))
+			}
+			None => {
+				let main_ty = i32_type.fn_type(&[], false);
+
+				module.add_function("main", main_ty, None)
+			}
+		};
 
 		let lifetime = {
 			let lifetime_start = get_intrinsic_function_from_name(
@@ -57,7 +212,19 @@ impl<'ctx> AssemblerFunctions<'ctx> {
 
 		let this = Self {
 			getchar,
+			getchar_into,
 			putchar,
+			flush,
+			set_output_threshold,
+			alloc,
+			free,
+			grow_tape,
+			tape_out_of_bounds,
+			cell_overflow,
+			snapshot_tape,
+			start_meter,
+			refuel,
+			finish_meter,
 			main,
 			lifetime,
 		};
@@ -80,13 +247,17 @@ impl<'ctx> AssemblerFunctions<'ctx> {
 		let nofree_attr = context.create_named_enum_attribute("nofree", 0b0);
 		let norecurse_attr = context.create_named_enum_attribute("norecurse", 0b0);
 		let willreturn_attr = context.create_named_enum_attribute("willreturn", 0b0);
-		let arg_none_inaccessable_read_memory_attr =
-			context.create_named_enum_attribute("memory", INACCESSABLE_READ);
+		// output is buffered, so every I/O function both reads and writes the buffer
+		let arg_none_inaccessable_readwrite_memory_attr =
+			context.create_named_enum_attribute("memory", INACCESSABLE_READ | INACCESSABLE_WRITE);
 		let zeroext_attr = context.create_named_enum_attribute("zeroext", 0b0);
-		let arg_read_inaccessable_write_memory_attr =
-			context.create_named_enum_attribute("memory", ARG_READ | INACCESSABLE_WRITE);
+		let arg_read_inaccessable_readwrite_memory_attr = context.create_named_enum_attribute(
+			"memory",
+			ARG_READ | INACCESSABLE_READ | INACCESSABLE_WRITE,
+		);
 		let noundef_attr = context.create_named_enum_attribute("noundef", 0b0);
 		let nounwind_attr = context.create_named_enum_attribute("nounwind", 0b0);
+		let cold_attr = context.create_named_enum_attribute("cold", 0b0);
 		let target_cpu_attr = context.create_string_attribute("target-cpu", cpu_name);
 		let target_cpu_features_attr =
 			context.create_string_attribute("target-features", cpu_features);
@@ -99,7 +270,7 @@ impl<'ctx> AssemblerFunctions<'ctx> {
 				AppliedAttribute::Function(nofree_attr),
 				AppliedAttribute::Function(norecurse_attr),
 				AppliedAttribute::Function(willreturn_attr),
-				AppliedAttribute::Function(arg_read_inaccessable_write_memory_attr),
+				AppliedAttribute::Function(arg_read_inaccessable_readwrite_memory_attr),
 				AppliedAttribute::Function(probe_stack_attr),
 				AppliedAttribute::Function(target_cpu_attr),
 				AppliedAttribute::Function(target_cpu_features_attr),
@@ -114,11 +285,125 @@ impl<'ctx> AssemblerFunctions<'ctx> {
 				AppliedAttribute::Function(nocallback_attr),
 				AppliedAttribute::Function(nofree_attr),
 				AppliedAttribute::Function(norecurse_attr),
-				AppliedAttribute::Function(arg_none_inaccessable_read_memory_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
 				AppliedAttribute::Function(probe_stack_attr),
 				AppliedAttribute::Function(target_cpu_attr),
 				AppliedAttribute::Function(target_cpu_features_attr),
-				AppliedAttribute::Return(zeroext_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+				AppliedAttribute::Return(noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.getchar_into,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(probe_stack_attr),
+				AppliedAttribute::Function(target_cpu_attr),
+				AppliedAttribute::Function(target_cpu_features_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+				AppliedAttribute::Param(1, noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.flush,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
+			],
+		);
+		add_attributes_to(
+			self.set_output_threshold,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.grow_tape,
+			[
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+				AppliedAttribute::Param(1, noundef_attr),
+				AppliedAttribute::Param(2, noundef_attr),
+				AppliedAttribute::Param(3, noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.tape_out_of_bounds,
+			[
+				AppliedAttribute::Function(cold_attr),
+				AppliedAttribute::Function(nounwind_attr),
+			],
+		);
+		add_attributes_to(
+			self.cell_overflow,
+			[
+				AppliedAttribute::Function(cold_attr),
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Param(3, zeroext_attr),
+			],
+		);
+		add_attributes_to(
+			self.snapshot_tape,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_read_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+				AppliedAttribute::Param(1, noundef_attr),
+				AppliedAttribute::Param(2, noundef_attr),
+				AppliedAttribute::Param(3, noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.start_meter,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Param(0, noundef_attr),
+				AppliedAttribute::Param(1, noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.refuel,
+			[
+				AppliedAttribute::Function(cold_attr),
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
+				AppliedAttribute::Return(noundef_attr),
+			],
+		);
+		add_attributes_to(
+			self.finish_meter,
+			[
+				AppliedAttribute::Function(nocallback_attr),
+				AppliedAttribute::Function(nofree_attr),
+				AppliedAttribute::Function(norecurse_attr),
+				AppliedAttribute::Function(willreturn_attr),
+				AppliedAttribute::Function(arg_none_inaccessable_readwrite_memory_attr),
+				AppliedAttribute::Function(nounwind_attr),
 				AppliedAttribute::Return(noundef_attr),
 			],
 		);
@@ -0,0 +1,36 @@
+use inkwell::values::{FunctionValue, IntValue, PointerValue};
+
+/// The parameters of a program compiled into a library function, which stand in for the
+/// runtime's tape and I/O.
+#[derive(Debug, Clone, Copy)]
+pub struct LibraryParams<'ctx> {
+	pub tape: PointerValue<'ctx>,
+	pub len: IntValue<'ctx>,
+	/// `int getc(void *ctx)`, returning a negative value on EOF.
+	pub getc: PointerValue<'ctx>,
+	/// `void putc(uint8_t value, void *ctx)`.
+	pub putc: PointerValue<'ctx>,
+	pub ctx: PointerValue<'ctx>,
+}
+
+impl<'ctx> LibraryParams<'ctx> {
+	pub fn new(function: FunctionValue<'ctx>) -> Option<Self> {
+		let param = |i| function.get_nth_param(i);
+
+		let this = Self {
+			tape: param(0)?.into_pointer_value(),
+			len: param(1)?.into_int_value(),
+			getc: param(2)?.into_pointer_value(),
+			putc: param(3)?.into_pointer_value(),
+			ctx: param(4)?.into_pointer_value(),
+		};
+
+		this.tape.set_name("tape");
+		this.len.set_name("len");
+		this.getc.set_name("getc");
+		this.putc.set_name("putc");
+		this.ctx.set_name("ctx");
+
+		Some(this)
+	}
+}
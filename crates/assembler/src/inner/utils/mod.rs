@@ -1,6 +1,7 @@
 mod debug_info;
 mod functions;
+mod library;
 mod pointers;
 mod type_cast;
 
-pub use self::{debug_info::*, functions::*, pointers::*, type_cast::*};
+pub use self::{debug_info::*, functions::*, library::*, pointers::*, type_cast::*};
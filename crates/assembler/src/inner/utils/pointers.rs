@@ -1,6 +1,6 @@
 use std::num::NonZero;
 
-use frick_spec::{POINTER_SIZE, TAPE_SIZE};
+use frick_spec::{POINTER_SIZE, Spec, TapeBoundary};
 use frick_utils::Convert as _;
 use inkwell::{
 	builder::Builder,
@@ -10,44 +10,80 @@ use inkwell::{
 	values::{BasicMetadataValueEnum, PointerValue},
 };
 
-use super::AssemblerFunctions;
-use crate::{AssemblyError, IntoContext as _};
+use super::{AssemblerFunctions, LibraryParams};
+use crate::{AssemblyError, ContextExt as _, IntoContext as _};
 
 const TAPE_ALIGNMENT: u32 = 4;
 
 #[derive(Debug, Clone, Copy)]
 pub struct AssemblerPointers<'ctx> {
+	/// The tape itself, or a pointer to the heap allocated tape when it can grow.
 	pub tape: PointerValue<'ctx>,
 	pub pointer: PointerValue<'ctx>,
+	/// The current length of a growable tape.
+	pub tape_len: Option<PointerValue<'ctx>>,
+	/// Loop iterations left before a metered program checks its limits again.
+	pub fuel: Option<PointerValue<'ctx>>,
 }
 
 impl<'ctx> AssemblerPointers<'ctx> {
-	pub fn new(module: &Module<'ctx>, builder: &Builder<'ctx>) -> Result<Self, AssemblyError> {
+	pub fn new(
+		module: &Module<'ctx>,
+		builder: &Builder<'ctx>,
+		spec: Spec,
+		library: Option<LibraryParams<'ctx>>,
+	) -> Result<Self, AssemblyError> {
 		let context = module.get_context();
 
-		let cell_type = context.i8_type();
+		let cell_type = context.cell_type(spec.cell_width());
 		let ptr_int_type = context
 			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
-		let tape = {
-			let tape_type = cell_type.array_type(TAPE_SIZE as u32);
+		let tape = match library {
+			Some(library) => library.tape,
+			None => match spec.boundary() {
+				TapeBoundary::Grow => builder.build_alloca(context.default_ptr_type(), "tape\0")?,
+				TapeBoundary::Wrap | TapeBoundary::Abort => {
+					let tape_type = cell_type.array_type(spec.tape_size() as u32);
 
-			builder.build_alloca(tape_type, "tape")?
-		};
+					let tape = builder.build_alloca(tape_type, "tape")?;
 
-		if let Some(tape_instr) = tape.as_instruction() {
-			tape_instr.set_alignment(TAPE_ALIGNMENT)?;
-		}
+					if let Some(tape_instr) = tape.as_instruction() {
+						tape_instr.set_alignment(TAPE_ALIGNMENT)?;
+					}
+
+					tape
+				}
+			},
+		};
 
 		let pointer = builder.build_alloca(ptr_int_type, "pointer\0")?;
 
-		Ok(Self { tape, pointer })
+		let tape_len = match spec.boundary() {
+			TapeBoundary::Grow => Some(builder.build_alloca(ptr_int_type, "tape_len\0")?),
+			TapeBoundary::Wrap | TapeBoundary::Abort => None,
+		};
+
+		let fuel = if spec.is_metered() {
+			Some(builder.build_alloca(context.i64_type(), "fuel\0")?)
+		} else {
+			None
+		};
+
+		Ok(Self {
+			tape,
+			pointer,
+			tape_len,
+			fuel,
+		})
 	}
 
 	pub fn setup(
 		self,
 		builder: &Builder<'ctx>,
 		functions: AssemblerFunctions<'ctx>,
+		spec: Spec,
+		library: Option<LibraryParams<'ctx>>,
 	) -> Result<(), AssemblyError> {
 		let context = self.into_context();
 
@@ -56,13 +92,17 @@ impl<'ctx> AssemblerPointers<'ctx> {
 		let ptr_int_type = context
 			.custom_width_int_type(unsafe { NonZero::new_unchecked(POINTER_SIZE as u32) })?;
 
-		let tape_array_size = i64_type.const_int(TAPE_SIZE as u64, false);
+		let tape_array_size =
+			i64_type.const_int((spec.tape_size() * spec.cell_width().bytes()) as u64, false);
 
-		builder.build_call(
-			functions.lifetime.start,
-			&[self.tape.convert::<BasicMetadataValueEnum<'ctx>>()],
-			"\0",
-		)?;
+		// a caller owned tape is neither ours to zero nor an alloca with a lifetime
+		if library.is_none() {
+			builder.build_call(
+				functions.lifetime.start,
+				&[self.tape.convert::<BasicMetadataValueEnum<'ctx>>()],
+				"\0",
+			)?;
+		}
 
 		builder.build_call(
 			functions.lifetime.start,
@@ -72,9 +112,53 @@ impl<'ctx> AssemblerPointers<'ctx> {
 
 		let i8_zero = i8_type.const_zero();
 
-		builder.build_memset(self.tape, TAPE_ALIGNMENT, i8_zero, tape_array_size)?;
+		if let Some(tape_len) = self.tape_len {
+			let tape_len_value = ptr_int_type.const_int(spec.tape_size() as u64, false);
+
+			let heap_tape = builder
+				.build_direct_call(
+					functions.alloc,
+					&[tape_array_size.convert::<BasicMetadataValueEnum<'ctx>>()],
+					"\0",
+				)?
+				.try_as_basic_value()
+				.unwrap_basic()
+				.into_pointer_value();
+
+			builder.build_memset(heap_tape, 1, i8_zero, tape_array_size)?;
+			builder.build_store(self.tape, heap_tape)?;
+			builder.build_store(tape_len, tape_len_value)?;
+		} else if library.is_none() {
+			builder.build_memset(self.tape, TAPE_ALIGNMENT, i8_zero, tape_array_size)?;
+		}
+
 		builder.build_store(self.pointer, ptr_int_type.const_zero())?;
 
+		if let Some(fuel) = self.fuel {
+			// starting empty makes the first iteration check in with the runtime
+			builder.build_store(fuel, i64_type.const_zero())?;
+
+			let step_limit = spec.step_limit().unwrap_or(u64::MAX);
+			// u64::MAX is reserved for no timeout
+			let timeout_millis = spec.timeout().map_or(u64::MAX, |timeout| {
+				u64::try_from(timeout.as_millis())
+					.map_or(u64::MAX - 1, |millis| millis.min(u64::MAX - 1))
+			});
+
+			builder.build_direct_call(
+				functions.start_meter,
+				&[
+					i64_type
+						.const_int(step_limit, false)
+						.convert::<BasicMetadataValueEnum<'ctx>>(),
+					i64_type
+						.const_int(timeout_millis, false)
+						.convert::<BasicMetadataValueEnum<'ctx>>(),
+				],
+				"\0",
+			)?;
+		}
+
 		Ok(())
 	}
 }
@@ -3,16 +3,24 @@
 mod error;
 mod ext;
 mod inner;
+mod library;
+mod link;
 mod module;
+#[cfg(unix)]
+mod sandbox;
 
 use std::{
 	ffi::CStr,
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
 	fs,
+	io::prelude::*,
 	path::{Path, PathBuf},
 };
 
 use frick_instructions::BrainInstruction;
+pub use frick_interop::{SourceLocation, StopReason, TapeSnapshot, Trap};
+use frick_serialize::{Artifact, EmitOptions};
+use frick_spec::{Spec, TapeBoundary};
 use inkwell::{
 	OptimizationLevel,
 	context::Context,
@@ -29,23 +37,33 @@ use inkwell::{
 		CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
 		TargetMachineOptions,
 	},
+	values::FunctionValue,
 };
 use libc::c_void;
 use tracing::info;
 
 pub(crate) use self::ext::*;
-use self::inner::{AssemblerFunctions, InnerAssembler};
-pub use self::{error::*, module::AssembledModule};
+#[cfg(unix)]
+use self::sandbox::PrivateDir;
+#[cfg(unix)]
+pub use self::sandbox::{Sandbox, SandboxOutput};
+pub use self::{error::*, link::Linker, module::AssembledModule};
+use self::{
+	inner::{AssemblerFunctions, InnerAssembler},
+	link::ObjectFile,
+};
 
 pub struct Assembler {
 	context: Context,
 	passes: String,
 	file_path: PathBuf,
+	file_data: String,
+	spec: Spec,
 }
 
 impl Assembler {
 	#[must_use]
-	pub fn new(passes: String, file_path: PathBuf) -> Self {
+	pub fn new(passes: String, file_path: PathBuf, file_data: String) -> Self {
 		inkwell::support::error_handling::reset_fatal_error_handler();
 		unsafe {
 			inkwell::support::error_handling::install_fatal_error_handler(handler);
@@ -57,15 +75,256 @@ impl Assembler {
 			context: Context::create(),
 			passes,
 			file_path,
+			file_data,
+			spec: Spec::default(),
 		}
 	}
 
+	#[must_use]
+	pub const fn with_spec(mut self, spec: Spec) -> Self {
+		self.spec = spec;
+		self
+	}
+
 	#[tracing::instrument(skip_all, fields(indicatif.pb_show = tracing::field::Empty))]
 	pub fn assemble<'ctx>(
 		&'ctx self,
 		instrs: &[BrainInstruction],
-		output_path: &Path,
+		emit: EmitOptions<'_>,
 	) -> Result<AssembledModule<'ctx>, AssemblyError> {
+		let (module, main, ..) = self.compile(instrs, emit, CodegenMode::Jit, None)?;
+
+		info!("creating JIT execution engine");
+		let execution_engine = module.create_jit_execution_engine(OptimizationLevel::Aggressive)?;
+
+		if let Some(getchar) = module.get_function("rust_getchar\0") {
+			info!("adding rust_getchar to execution engine");
+			execution_engine
+				.add_global_mapping(&getchar, (frick_interop::rust_getchar as *const ()).addr());
+		}
+
+		if let Some(getchar_into) = module.get_function("rust_getchar_into\0") {
+			info!("adding rust_getchar_into to execution engine");
+			execution_engine.add_global_mapping(
+				&getchar_into,
+				(frick_interop::rust_getchar_into as *const ()).addr(),
+			);
+		}
+
+		if let Some(putchar) = module.get_function("rust_putchar\0") {
+			info!("adding rust_putchar to execution engine");
+			execution_engine
+				.add_global_mapping(&putchar, (frick_interop::rust_putchar as *const ()).addr());
+		}
+
+		if let Some(flush) = module.get_function("rust_flush\0") {
+			info!("adding rust_flush to execution engine");
+			execution_engine
+				.add_global_mapping(&flush, (frick_interop::rust_flush as *const ()).addr());
+		}
+
+		if let Some(set_output_threshold) = module.get_function("rust_set_output_threshold\0") {
+			info!("adding rust_set_output_threshold to execution engine");
+			execution_engine.add_global_mapping(
+				&set_output_threshold,
+				(frick_interop::rust_set_output_threshold as *const ()).addr(),
+			);
+		}
+
+		if let Some(alloc) = module.get_function("rust_alloc\0") {
+			info!("adding rust_alloc to execution engine");
+			execution_engine
+				.add_global_mapping(&alloc, (frick_interop::rust_alloc as *const ()).addr());
+		}
+
+		if let Some(free) = module.get_function("rust_free\0") {
+			info!("adding rust_free to execution engine");
+			execution_engine
+				.add_global_mapping(&free, (frick_interop::rust_free as *const ()).addr());
+		}
+
+		if let Some(grow_tape) = module.get_function("rust_grow_tape\0") {
+			info!("adding rust_grow_tape to execution engine");
+			execution_engine.add_global_mapping(
+				&grow_tape,
+				(frick_interop::rust_grow_tape as *const ()).addr(),
+			);
+		}
+
+		if let Some(snapshot_tape) = module.get_function("rust_snapshot_tape\0") {
+			info!("adding rust_snapshot_tape to execution engine");
+			execution_engine.add_global_mapping(
+				&snapshot_tape,
+				(frick_interop::rust_snapshot_tape as *const ()).addr(),
+			);
+		}
+
+		if let Some(start_meter) = module.get_function("rust_start_meter\0") {
+			info!("adding rust_start_meter to execution engine");
+			execution_engine.add_global_mapping(
+				&start_meter,
+				(frick_interop::rust_start_meter as *const ()).addr(),
+			);
+		}
+
+		if let Some(refuel) = module.get_function("rust_refuel\0") {
+			info!("adding rust_refuel to execution engine");
+			execution_engine
+				.add_global_mapping(&refuel, (frick_interop::rust_refuel as *const ()).addr());
+		}
+
+		if let Some(finish_meter) = module.get_function("rust_finish_meter\0") {
+			info!("adding rust_finish_meter to execution engine");
+			execution_engine.add_global_mapping(
+				&finish_meter,
+				(frick_interop::rust_finish_meter as *const ()).addr(),
+			);
+		}
+
+		if let Some(tape_out_of_bounds) = module.get_function("rust_tape_out_of_bounds\0") {
+			info!("adding rust_tape_out_of_bounds to execution engine");
+			execution_engine.add_global_mapping(
+				&tape_out_of_bounds,
+				(frick_interop::rust_tape_out_of_bounds as *const ()).addr(),
+			);
+		}
+
+		if let Some(cell_overflow) = module.get_function("rust_cell_overflow\0") {
+			info!("adding rust_cell_overflow to execution engine");
+			execution_engine.add_global_mapping(
+				&cell_overflow,
+				(frick_interop::rust_cell_overflow as *const ()).addr(),
+			);
+		}
+
+		Ok(AssembledModule {
+			execution_engine,
+			main,
+			strict_semantics: self.spec.strict_semantics(),
+		})
+	}
+
+	/// Builds the program into an executable and runs it in a child process, so that neither
+	/// crashes nor the runtime aborting can bring down the caller.
+	///
+	/// The program is compiled and linked in the calling process and only ever run after an
+	/// `exec`, so nothing but async-signal-safe calls happen between `fork` and `exec`.
+	#[cfg(unix)]
+	#[tracing::instrument(skip_all, fields(indicatif.pb_show = tracing::field::Empty))]
+	pub fn run_sandboxed(
+		&self,
+		instrs: &[BrainInstruction],
+		emit: EmitOptions<'_>,
+		linker: &Linker,
+		sandbox: &Sandbox,
+		input: &[u8],
+	) -> Result<SandboxOutput, AssemblyError> {
+		let dir = PrivateDir::create()?;
+		let executable_path = dir.path().join("program");
+
+		self.build(instrs, emit, linker, &executable_path)?;
+
+		sandbox.run_executable(&executable_path, input)
+	}
+
+	#[tracing::instrument(skip_all, fields(indicatif.pb_show = tracing::field::Empty))]
+	pub fn build(
+		&self,
+		instrs: &[BrainInstruction],
+		emit: EmitOptions<'_>,
+		linker: &Linker,
+		executable_path: &Path,
+	) -> Result<(), AssemblyError> {
+		let (module, _, target_machine) = self.compile(instrs, emit, CodegenMode::Object, None)?;
+
+		// dropping `object` removes the file again, whether or not linking succeeds
+		let (object, mut object_file) = ObjectFile::create(executable_path)?;
+
+		info!("writing object file for linking");
+		let memory_buffer = target_machine.write_to_memory_buffer(&module, FileType::Object)?;
+		object_file.write_all(memory_buffer.as_slice())?;
+		drop(object_file);
+
+		info!("linking executable");
+		linker.link(object.path(), executable_path)
+	}
+
+	/// Compiles the program into `int {name}(uint8_t *tape, size_t len, getc_fn, putc_fn, void *ctx)`,
+	/// writing `{name}.o`, `lib{name}.a`, and `{name}.h` into `output_dir`.
+	///
+	/// The function has no dependency on the frick runtime, so the tape is owned by the caller and
+	/// all I/O goes through the given callbacks. The caller's tape can hold anything and is read
+	/// back afterwards, so `instrs` have to be optimized with strict semantics.
+	#[tracing::instrument(skip(self, instrs, emit), fields(indicatif.pb_show = tracing::field::Empty))]
+	pub fn build_library(
+		&self,
+		instrs: &[BrainInstruction],
+		emit: EmitOptions<'_>,
+		name: &str,
+		output_dir: &Path,
+	) -> Result<(), AssemblyError> {
+		library::validate_name(name)?;
+
+		if !self.spec.strict_semantics() {
+			return Err(AssemblyError::NeedsStrictSemantics("a library function"));
+		}
+
+		if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			return Err(AssemblyError::UnsupportedInLibrary("a growable tape"));
+		}
+
+		if self.spec.is_metered() {
+			return Err(AssemblyError::UnsupportedInLibrary("metering"));
+		}
+
+		let (module, _, target_machine) =
+			self.compile(instrs, emit, CodegenMode::Object, Some(name))?;
+
+		fs::create_dir_all(output_dir)?;
+
+		let object_path = output_dir.join(format!("{name}.o"));
+
+		info!("writing object file");
+		target_machine.write_to_file(&module, FileType::Object, &object_path)?;
+
+		info!("archiving static library");
+		library::archive(&object_path, &output_dir.join(format!("lib{name}.a")))?;
+
+		info!("writing header");
+		fs::write(
+			output_dir.join(format!("{name}.h")),
+			library::header(name, self.spec),
+		)?;
+
+		Ok(())
+	}
+
+	#[tracing::instrument(skip(self, instrs), fields(indicatif.pb_show = tracing::field::Empty))]
+	pub fn emit(
+		&self,
+		instrs: &[BrainInstruction],
+		output_path: &Path,
+		artifact: Artifact,
+	) -> Result<(), AssemblyError> {
+		let (module, _, target_machine) =
+			self.compile(instrs, EmitOptions::none(), CodegenMode::Object, None)?;
+
+		write_artifact(
+			&target_machine,
+			&module,
+			output_path,
+			ToWriteType::Optimized,
+			artifact,
+		)
+	}
+
+	fn compile<'ctx>(
+		&'ctx self,
+		instrs: &[BrainInstruction],
+		emit: EmitOptions<'_>,
+		mode: CodegenMode,
+		library_name: Option<&str>,
+	) -> Result<(Module<'ctx>, FunctionValue<'ctx>, TargetMachine), AssemblyError> {
 		info!("initializing all targets");
 
 		Target::initialize_native(&InitializationConfig::default())?;
@@ -93,8 +352,8 @@ impl Assembler {
 			let options = TargetMachineOptions::new()
 				.set_cpu(&cpu)
 				.set_features(&cpu_features)
-				.set_reloc_mode(RelocMode::Default)
-				.set_code_model(CodeModel::JITDefault)
+				.set_reloc_mode(mode.reloc_mode())
+				.set_code_model(mode.code_model())
 				.set_level(OptimizationLevel::Aggressive);
 
 			target
@@ -113,6 +372,9 @@ impl Assembler {
 			&cpu,
 			&cpu_features,
 			&self.file_path,
+			&self.file_data,
+			self.spec,
+			library_name,
 		)?;
 
 		let (module, AssemblerFunctions { main, .. }, target_machine) =
@@ -121,12 +383,7 @@ impl Assembler {
 		info!("verifying emitted LLVM IR");
 		module.verify()?;
 
-		write_data(
-			&target_machine,
-			&module,
-			output_path,
-			ToWriteType::Unoptimized,
-		)?;
+		write_data(&target_machine, &module, emit, ToWriteType::Unoptimized)?;
 
 		let pass_options = PassBuilderOptions::create();
 
@@ -147,32 +404,9 @@ impl Assembler {
 		info!("verifying optimized LLVM IR");
 		module.verify()?;
 
-		write_data(
-			&target_machine,
-			&module,
-			output_path,
-			ToWriteType::Optimized,
-		)?;
-
-		info!("creating JIT execution engine");
-		let execution_engine = module.create_jit_execution_engine(OptimizationLevel::Aggressive)?;
-
-		if let Some(getchar) = module.get_function("rust_getchar\0") {
-			info!("adding rust_getchar to execution engine");
-			execution_engine
-				.add_global_mapping(&getchar, (frick_interop::rust_getchar as *const ()).addr());
-		}
+		write_data(&target_machine, &module, emit, ToWriteType::Optimized)?;
 
-		if let Some(putchar) = module.get_function("rust_putchar\0") {
-			info!("adding rust_putchar to execution engine");
-			execution_engine
-				.add_global_mapping(&putchar, (frick_interop::rust_putchar as *const ()).addr());
-		}
-
-		Ok(AssembledModule {
-			execution_engine,
-			main,
-		})
+		Ok((module, main, target_machine))
 	}
 }
 
@@ -187,44 +421,65 @@ extern "C" fn handler(ptr: *const i8) {
 
 #[tracing::instrument(skip_all, fields(%opt_type))]
 fn write_data(
+	target_machine: &TargetMachine,
+	module: &Module<'_>,
+	emit: EmitOptions<'_>,
+	opt_type: ToWriteType,
+) -> Result<(), AssemblyError> {
+	for artifact in [
+		Artifact::LlvmIr,
+		Artifact::Bitcode,
+		Artifact::Object,
+		Artifact::Assembly,
+	] {
+		if let Some(output_path) = emit.folder_for(artifact) {
+			write_artifact(target_machine, module, output_path, opt_type, artifact)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn write_artifact(
 	target_machine: &TargetMachine,
 	module: &Module<'_>,
 	output_path: &Path,
 	opt_type: ToWriteType,
+	artifact: Artifact,
 ) -> Result<(), AssemblyError> {
-	info!("writing LLVM IR");
+	let file_path = output_path.join(format!("{opt_type}.{}", artifact.extension()));
 
-	{
-		let s = module.print_to_string().to_string();
-		fs::write(output_path.join(format!("{opt_type}.ll")), s)?;
-	}
+	match artifact {
+		Artifact::LlvmIr => {
+			info!("writing LLVM IR");
 
-	info!("writing LLVM bitcode");
+			let s = module.print_to_string().to_string();
+			fs::write(file_path, s)?;
+		}
+		Artifact::Bitcode => {
+			info!("writing LLVM bitcode");
 
-	{
-		let memory_buf = module.write_bitcode_to_memory();
-		fs::write(
-			output_path.join(format!("{opt_type}.bc")),
-			memory_buf.as_slice(),
-		)?;
-	}
+			let memory_buf = module.write_bitcode_to_memory();
+			fs::write(file_path, memory_buf.as_slice())?;
+		}
+		Artifact::Object => {
+			info!("writing object file");
 
-	info!("writing object file");
-	{
-		let memory_buffer = target_machine.write_to_memory_buffer(module, FileType::Object)?;
-		fs::write(
-			output_path.join(format!("{opt_type}.o")),
-			memory_buffer.as_slice(),
-		)?;
-	}
+			let memory_buffer = target_machine.write_to_memory_buffer(module, FileType::Object)?;
+			fs::write(file_path, memory_buffer.as_slice())?;
+		}
+		Artifact::Assembly => {
+			info!("writing assembly");
 
-	info!("writing assembly");
-	{
-		let memory_buffer = target_machine.write_to_memory_buffer(module, FileType::Assembly)?;
-		fs::write(
-			output_path.join(format!("{opt_type}.s")),
-			memory_buffer.as_slice(),
-		)?;
+			let memory_buffer =
+				target_machine.write_to_memory_buffer(module, FileType::Assembly)?;
+			fs::write(file_path, memory_buffer.as_slice())?;
+		}
+		// these are written by the optimizer and transpiler, not the assembler
+		Artifact::Operations
+		| Artifact::Instructions
+		| Artifact::CSource
+		| Artifact::RustSource => {}
 	}
 
 	Ok(())
@@ -245,6 +500,28 @@ impl Display for ToWriteType {
 	}
 }
 
+#[derive(Debug, Clone, Copy)]
+enum CodegenMode {
+	Jit,
+	Object,
+}
+
+impl CodegenMode {
+	const fn reloc_mode(self) -> RelocMode {
+		match self {
+			Self::Jit => RelocMode::Default,
+			Self::Object => RelocMode::PIC,
+		}
+	}
+
+	const fn code_model(self) -> CodeModel {
+		match self {
+			Self::Jit => CodeModel::JITDefault,
+			Self::Object => CodeModel::Default,
+		}
+	}
+}
+
 extern "C" fn llvm_diagnostic_handler(di: LLVMDiagnosticInfoRef, _ctx: *mut c_void) {
 	let message = unsafe { CStr::from_ptr(LLVMGetDiagInfoDescription(di)) }.to_string_lossy();
 
@@ -256,3 +533,90 @@ extern "C" fn llvm_diagnostic_handler(di: LLVMDiagnosticInfoRef, _ctx: *mut c_vo
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		env, fs,
+		path::{Path, PathBuf},
+		process::{self, Command},
+	};
+
+	use frick_optimizer::Optimizer;
+	use frick_serialize::EmitOptions;
+	use frick_spec::Spec;
+
+	use super::{Assembler, AssemblyError};
+
+	const HARNESS: &str = r#"
+#include <stdio.h>
+#include "copy.h"
+
+static int getc_fn(void *ctx) { return -1; }
+static void putc_fn(uint8_t value, void *ctx) {}
+
+int main(void) {
+	static uint8_t tape[COPY_TAPE_SIZE];
+	tape[0] = 5;
+	tape[1] = 2;
+
+	int status = copy(tape, COPY_TAPE_SIZE, getc_fn, putc_fn, NULL);
+
+	printf("%d %d %d", status, tape[0], tape[1]);
+
+	return 0;
+}
+"#;
+
+	fn build_library(program: &str, spec: Spec, dir: &Path) -> Result<(), AssemblyError> {
+		let ops = frick_operations::parse_str(program).unwrap();
+		let instrs = Optimizer::run(ops, spec, EmitOptions::none(), &mut Vec::new()).unwrap();
+
+		Assembler::new(
+			"default<O0>".to_owned(),
+			PathBuf::from("copy.bf"),
+			program.to_owned(),
+		)
+		.with_spec(spec)
+		.build_library(&instrs, EmitOptions::none(), "copy", dir)
+	}
+
+	#[test]
+	fn copy_loop_runs_on_caller_tape() {
+		let cc = env::var_os("CC").unwrap_or_else(|| "cc".into());
+
+		if Command::new(&cc).arg("--version").output().is_err() {
+			return;
+		}
+
+		let dir = env::temp_dir().join(format!("frick-library-{}", process::id()));
+
+		build_library("[->+<]", Spec::new().with_strict_semantics(true), &dir).unwrap();
+
+		fs::write(dir.join("main.c"), HARNESS).unwrap();
+
+		let compiled = Command::new(&cc)
+			.current_dir(&dir)
+			.args(["main.c", "libcopy.a", "-o", "main"])
+			.status()
+			.unwrap();
+
+		assert!(compiled.success());
+
+		let output = Command::new(dir.join("main")).output().unwrap();
+
+		_ = fs::remove_dir_all(&dir);
+
+		assert_eq!(String::from_utf8_lossy(&output.stdout), "0 0 7");
+	}
+
+	#[test]
+	fn library_needs_strict_semantics() {
+		let dir = env::temp_dir().join(format!("frick-library-loose-{}", process::id()));
+
+		assert!(matches!(
+			build_library("[->+<]", Spec::new(), &dir),
+			Err(AssemblyError::NeedsStrictSemantics(..))
+		));
+	}
+}
@@ -0,0 +1,108 @@
+use std::{env, ffi::OsString, fmt::Write as _, path::Path, process::Command};
+
+use frick_spec::Spec;
+use tracing::info;
+
+use super::AssemblyError;
+
+/// Returned by a library function whose program moved the pointer off either end of the tape.
+pub(crate) const OUT_OF_BOUNDS: u64 = 1;
+/// Returned by a library function handed a tape shorter than the spec's tape size.
+pub(crate) const TAPE_TOO_SHORT: u64 = 2;
+/// Returned by a library function whose program went past either end of a cell's range.
+pub(crate) const CELL_OVERFLOW: u64 = 3;
+
+pub(crate) fn validate_name(name: &str) -> Result<(), AssemblyError> {
+	let mut chars = name.chars();
+
+	let is_valid = chars
+		.next()
+		.is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& name != "main";
+
+	if is_valid {
+		Ok(())
+	} else {
+		Err(AssemblyError::InvalidLibraryName(name.to_owned()))
+	}
+}
+
+/// The C header declaring a library function built by
+/// [`Assembler::build_library`](crate::Assembler::build_library).
+pub(crate) fn header(name: &str, spec: Spec) -> String {
+	let guard = name.to_ascii_uppercase();
+	let tape_size = spec.tape_size();
+	let cell_bits = spec.cell_width().bits();
+
+	let mut header = String::new();
+
+	// writing into a string can't fail
+	_ = write!(
+		header,
+		"/* generated by frick */
+
+#ifndef {guard}_H
+#define {guard}_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+/* the fewest cells the tape passed to {name} may have */
+#define {guard}_TAPE_SIZE ((size_t){tape_size})
+
+#define {guard}_OK 0
+/* the program moved the pointer off either end of the tape */
+#define {guard}_OUT_OF_BOUNDS {OUT_OF_BOUNDS}
+/* the tape was shorter than {guard}_TAPE_SIZE */
+#define {guard}_TAPE_TOO_SHORT {TAPE_TOO_SHORT}
+/* a cell was incremented past its maximum or decremented below zero */
+#define {guard}_CELL_OVERFLOW {CELL_OVERFLOW}
+
+/* returns the next byte of input, or a negative value once input has run out */
+typedef int (*{name}_getc_fn)(void *ctx);
+typedef void (*{name}_putc_fn)(uint8_t value, void *ctx);
+
+/* runs the program on a caller owned tape, starting from the first cell */
+int {name}(uint{cell_bits}_t *tape, size_t len, {name}_getc_fn getc_fn, {name}_putc_fn putc_fn, void *ctx);
+
+#ifdef __cplusplus
+}}
+#endif
+
+#endif
+"
+	);
+
+	header
+}
+
+#[tracing::instrument]
+pub(crate) fn archive(object_path: &Path, archive_path: &Path) -> Result<(), AssemblyError> {
+	let program = env::var_os("AR").unwrap_or_else(|| OsString::from("ar"));
+
+	info!(?program);
+
+	// `ar` only ever adds to an existing archive
+	if archive_path.exists() {
+		std::fs::remove_file(archive_path)?;
+	}
+
+	let output = Command::new(program)
+		.arg("rcs")
+		.arg(archive_path)
+		.arg(object_path)
+		.output()?;
+
+	if !output.status.success() {
+		tracing::error!("{}", String::from_utf8_lossy(&output.stderr));
+
+		return Err(AssemblyError::ArchiverFailed(output.status));
+	}
+
+	Ok(())
+}
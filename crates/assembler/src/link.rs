@@ -0,0 +1,143 @@
+use std::{
+	env,
+	ffi::OsString,
+	fs::{self, File},
+	path::{Path, PathBuf},
+	process::{self, Command},
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use tracing::info;
+
+use super::AssemblyError;
+
+#[cfg(not(windows))]
+const RUNTIME_LIBRARY_NAME: &str = "libfrick_interop.a";
+#[cfg(windows)]
+const RUNTIME_LIBRARY_NAME: &str = "frick_interop.lib";
+
+// These mirror `rustc --print native-static-libs` for the interop staticlib
+#[cfg(target_os = "linux")]
+const NATIVE_LIBRARIES: &[&str] = &[
+	"-lgcc_s",
+	"-lutil",
+	"-lrt",
+	"-lpthread",
+	"-lm",
+	"-ldl",
+	"-lc",
+];
+#[cfg(target_os = "macos")]
+const NATIVE_LIBRARIES: &[&str] = &["-lSystem", "-lc", "-lm"];
+#[cfg(windows)]
+const NATIVE_LIBRARIES: &[&str] = &[
+	"kernel32.lib",
+	"advapi32.lib",
+	"ntdll.lib",
+	"userenv.lib",
+	"ws2_32.lib",
+	"dbghelp.lib",
+];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+const NATIVE_LIBRARIES: &[&str] = &[];
+
+#[derive(Debug, Clone)]
+pub struct Linker {
+	program: OsString,
+	runtime_path: PathBuf,
+}
+
+impl Linker {
+	#[must_use]
+	pub fn new(runtime_path: PathBuf) -> Self {
+		Self {
+			program: env::var_os("CC").unwrap_or_else(|| OsString::from("cc")),
+			runtime_path,
+		}
+	}
+
+	pub fn from_current_exe() -> Result<Self, AssemblyError> {
+		let current_exe = env::current_exe()?;
+
+		let runtime_path = current_exe.parent().map_or_else(
+			|| PathBuf::from(RUNTIME_LIBRARY_NAME),
+			|dir| dir.join(RUNTIME_LIBRARY_NAME),
+		);
+
+		Ok(Self::new(runtime_path))
+	}
+
+	#[must_use]
+	pub fn with_program(mut self, program: impl Into<OsString>) -> Self {
+		self.program = program.into();
+		self
+	}
+
+	#[must_use]
+	pub fn runtime_path(&self) -> &Path {
+		&self.runtime_path
+	}
+
+	#[tracing::instrument(skip(self))]
+	pub fn link(&self, object_path: &Path, executable_path: &Path) -> Result<(), AssemblyError> {
+		if !self.runtime_path.is_file() {
+			return Err(AssemblyError::RuntimeNotFound(self.runtime_path.clone()));
+		}
+
+		info!(program = ?self.program, runtime = %self.runtime_path.display());
+
+		let output = Command::new(&self.program)
+			.arg(object_path)
+			.arg(&self.runtime_path)
+			.args(NATIVE_LIBRARIES)
+			.arg("-o")
+			.arg(executable_path)
+			.output()?;
+
+		if !output.status.success() {
+			tracing::error!("{}", String::from_utf8_lossy(&output.stderr));
+
+			return Err(AssemblyError::LinkerFailed(output.status));
+		}
+
+		Ok(())
+	}
+}
+
+/// An object file written next to the executable it is linked into, removed again once dropped.
+pub(crate) struct ObjectFile {
+	path: PathBuf,
+}
+
+impl ObjectFile {
+	/// Creates the file with `create_new`, so an existing file or symlink is never written through.
+	pub fn create(executable_path: &Path) -> Result<(Self, File), AssemblyError> {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+		let mut file_name = executable_path
+			.file_name()
+			.map_or_else(|| OsString::from("frick"), ToOwned::to_owned);
+
+		file_name.push(format!(
+			".{}-{}.o",
+			process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed)
+		));
+
+		let path = executable_path.with_file_name(file_name);
+
+		let file = File::options().write(true).create_new(true).open(&path)?;
+
+		Ok((Self { path }, file))
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for ObjectFile {
+	fn drop(&mut self) {
+		_ = fs::remove_file(&self.path);
+	}
+}
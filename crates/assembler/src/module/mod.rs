@@ -1,3 +1,6 @@
+use std::io::{Read, Write};
+
+use frick_interop::TapeSnapshot;
 use inkwell::{execution_engine::ExecutionEngine, values::FunctionValue};
 
 use super::AssemblyError;
@@ -5,15 +8,67 @@ use super::AssemblyError;
 pub struct AssembledModule<'ctx> {
 	pub(crate) execution_engine: ExecutionEngine<'ctx>,
 	pub(crate) main: FunctionValue<'ctx>,
+	pub(crate) strict_semantics: bool,
 }
 
 impl AssembledModule<'_> {
+	/// Programs compiled with a step limit or timeout return [`AssemblyError::Stopped`] when
+	/// they hit either.
 	pub fn execute(&self) -> Result<(), AssemblyError> {
-		unsafe {
-			self.execution_engine.run_function_as_main(self.main, &[]);
+		let (((), stopped), trap) = frick_interop::with_trap(|| {
+			frick_interop::with_meter(|| unsafe {
+				self.execution_engine.run_function_as_main(self.main, &[]);
+			})
+		});
+
+		if let Some(trap) = trap {
+			return Err(AssemblyError::Trapped(trap));
+		}
+
+		match stopped {
+			Some(reason) => Err(AssemblyError::Stopped(reason)),
+			None => Ok(()),
 		}
+	}
+
+	/// Executes the program with input read from `reader` and output written to `writer`
+	/// rather than the process stdin and stdout.
+	///
+	/// Returns [`AssemblyError::Io`] if either of them fails, and [`AssemblyError::Trapped`]
+	/// rather than exiting the process when the program goes out of bounds or overflows a cell.
+	pub fn execute_with(
+		&self,
+		reader: &mut impl Read,
+		writer: &mut impl Write,
+	) -> Result<(), AssemblyError> {
+		frick_interop::with_io(reader, writer, || self.execute())?
+	}
+
+	/// Executes the program, returning the tape and pointer as they were when it finished.
+	///
+	/// Without strict semantics the optimizer drops trailing work that has no output, so the
+	/// final tape is only kept when the program was compiled with
+	/// [`frick_spec::Spec::with_strict_semantics`], otherwise this returns
+	/// [`AssemblyError::NeedsStrictSemantics`].
+	pub fn execute_snapshot(&self) -> Result<TapeSnapshot, AssemblyError> {
+		if !self.strict_semantics {
+			return Err(AssemblyError::NeedsStrictSemantics("a tape snapshot"));
+		}
+
+		let (result, snapshot) = frick_interop::with_snapshot(|| self.execute());
+
+		result?;
 
-		Ok(())
+		snapshot.ok_or(AssemblyError::NoTapeSnapshot)
+	}
+
+	/// Like [`Self::execute_snapshot`], with I/O handled as in [`Self::execute_with`].
+	pub fn execute_snapshot_with(
+		&self,
+		reader: &mut impl Read,
+		writer: &mut impl Write,
+	) -> Result<TapeSnapshot, AssemblyError> {
+		frick_interop::with_io(reader, writer, || self.execute_snapshot())?
 	}
 }
 
@@ -22,3 +77,98 @@ impl Drop for AssembledModule<'_> {
 		self.execution_engine.free_fn_machine_code(self.main);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::{io, path::PathBuf};
+
+	use frick_interop::{
+		TapeSnapshot, Trap,
+		test_util::{self, Backend},
+	};
+	use frick_optimizer::Optimizer;
+	use frick_serialize::EmitOptions;
+	use frick_spec::Spec;
+
+	use crate::{AssembledModule, Assembler, AssemblyError};
+
+	struct Llvm;
+
+	impl Llvm {
+		fn run<R>(
+			program: &str,
+			spec: Spec,
+			f: impl FnOnce(&AssembledModule<'_>) -> Result<R, AssemblyError>,
+		) -> Result<R, AssemblyError> {
+			let ops = frick_operations::parse_str(program).unwrap();
+			let instrs = Optimizer::run(ops, spec, EmitOptions::none(), &mut Vec::new()).unwrap();
+
+			let assembler = Assembler::new(
+				"default<O0>".to_owned(),
+				PathBuf::from("test.bf"),
+				program.to_owned(),
+			)
+			.with_spec(spec);
+
+			f(&assembler.assemble(&instrs, EmitOptions::none())?)
+		}
+	}
+
+	impl Backend for Llvm {
+		type Error = AssemblyError;
+
+		fn execute(program: &str, spec: Spec) -> Result<(), Self::Error> {
+			Self::run(program, spec, AssembledModule::execute)
+		}
+
+		fn execute_with(
+			program: &str,
+			spec: Spec,
+			output: &mut Vec<u8>,
+		) -> Result<(), Self::Error> {
+			Self::run(program, spec, |module| {
+				module.execute_with(&mut io::empty(), output)
+			})
+		}
+
+		fn execute_snapshot_with(
+			program: &str,
+			spec: Spec,
+			output: &mut Vec<u8>,
+		) -> Result<TapeSnapshot, Self::Error> {
+			Self::run(program, spec, |module| {
+				module.execute_snapshot_with(&mut io::empty(), output)
+			})
+		}
+
+		fn trap(error: Self::Error) -> Option<Trap> {
+			match error {
+				AssemblyError::Trapped(trap) => Some(trap),
+				_ => None,
+			}
+		}
+	}
+
+	#[test]
+	fn snapshots() {
+		test_util::check_snapshots::<Llvm>();
+	}
+
+	#[test]
+	fn snapshot_needs_strict_semantics() {
+		assert!(matches!(
+			Llvm::execute_snapshot_with("+++>++", Spec::new(), &mut Vec::new()),
+			Err(AssemblyError::NeedsStrictSemantics(..))
+		));
+	}
+
+	#[test]
+	fn standalone_overflow_exits() {
+		test_util::check_standalone_overflow::<Llvm>("module::tests::standalone_overflow_exits");
+	}
+
+	#[test]
+	fn hosted_traps_are_returned() {
+		test_util::check_hosted_traps::<Llvm>();
+	}
+}
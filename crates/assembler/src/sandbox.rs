@@ -0,0 +1,289 @@
+use std::{
+	env,
+	fs::{self, DirBuilder},
+	io::{self, prelude::*},
+	os::unix::{
+		fs::DirBuilderExt as _,
+		process::{CommandExt as _, ExitStatusExt as _},
+	},
+	panic,
+	path::{Path, PathBuf},
+	process::{self, Command, ExitStatus, Stdio},
+	sync::atomic::{AtomicUsize, Ordering},
+	thread,
+	time::Duration,
+};
+
+use libc::{pid_t, rlim_t};
+use tracing::info;
+
+use super::AssemblyError;
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+type Resource = libc::c_int;
+
+/// Resource limits for running a program in a child process, so that crashes, aborts and
+/// runaway programs can't take the host down with them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sandbox {
+	memory_limit: Option<u64>,
+	cpu_limit: Option<Duration>,
+	output_limit: Option<usize>,
+}
+
+impl Sandbox {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			memory_limit: None,
+			cpu_limit: None,
+			output_limit: None,
+		}
+	}
+
+	/// Limits the address space of the child, in bytes.
+	#[must_use]
+	pub const fn with_memory_limit(mut self, memory_limit: u64) -> Self {
+		self.memory_limit = Some(memory_limit);
+		self
+	}
+
+	/// Limits the CPU time of the child, rounded up to whole seconds.
+	#[must_use]
+	pub const fn with_cpu_limit(mut self, cpu_limit: Duration) -> Self {
+		self.cpu_limit = Some(cpu_limit);
+		self
+	}
+
+	/// Kills the child once it writes more than this many bytes to either stdout or stderr.
+	#[must_use]
+	pub const fn with_output_limit(mut self, output_limit: usize) -> Self {
+		self.output_limit = Some(output_limit);
+		self
+	}
+
+	#[must_use]
+	pub const fn memory_limit(&self) -> Option<u64> {
+		self.memory_limit
+	}
+
+	#[must_use]
+	pub const fn cpu_limit(&self) -> Option<Duration> {
+		self.cpu_limit
+	}
+
+	#[must_use]
+	pub const fn output_limit(&self) -> Option<usize> {
+		self.output_limit
+	}
+
+	/// Runs an executable produced by [`Assembler::build`](crate::Assembler::build), feeding it
+	/// `input` on stdin.
+	#[tracing::instrument(skip(self, input))]
+	pub fn run_executable(
+		&self,
+		executable_path: &Path,
+		input: &[u8],
+	) -> Result<SandboxOutput, AssemblyError> {
+		let limits = *self;
+
+		let mut command = Command::new(executable_path);
+		command
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped());
+
+		// only async-signal-safe calls happen between fork and exec
+		unsafe {
+			command.pre_exec(move || limits.apply());
+		}
+
+		info!("spawning sandboxed executable");
+		let mut child = command.spawn()?;
+
+		let (Some(stdin), Some(stdout), Some(stderr)) =
+			(child.stdin.take(), child.stdout.take(), child.stderr.take())
+		else {
+			unreachable!("all standard streams were piped");
+		};
+
+		let pid = pid_t::try_from(child.id()).expect("process ids should fit in pid_t");
+
+		let captured = self.capture(pid, stdin, stdout, stderr, input);
+
+		let status = child.wait()?;
+
+		Ok(captured?.with_status(status))
+	}
+
+	fn capture(
+		&self,
+		pid: pid_t,
+		stdin: impl Write + Send,
+		stdout: impl Read + Send,
+		stderr: impl Read + Send,
+		input: &[u8],
+	) -> io::Result<SandboxOutput> {
+		let limit = self.output_limit.unwrap_or(usize::MAX);
+
+		thread::scope(|s| {
+			s.spawn(move || {
+				let mut stdin = stdin;
+
+				// a closed pipe only means the program stopped reading
+				_ = stdin.write_all(input);
+			});
+
+			let stderr = s.spawn(move || read_limited(stderr, limit, pid));
+			let (stdout, stdout_truncated) = read_limited(stdout, limit, pid)?;
+			let (stderr, stderr_truncated) =
+				stderr.join().unwrap_or_else(|e| panic::resume_unwind(e))?;
+
+			Ok(SandboxOutput {
+				status: ExitStatus::from_raw(0),
+				stdout,
+				stderr,
+				output_truncated: stdout_truncated || stderr_truncated,
+			})
+		})
+	}
+
+	fn apply(self) -> io::Result<()> {
+		set_limit(libc::RLIMIT_CORE, 0)?;
+
+		if let Some(memory_limit) = self.memory_limit {
+			set_limit(libc::RLIMIT_AS, memory_limit)?;
+		}
+
+		if let Some(cpu_limit) = self.cpu_limit {
+			let seconds = cpu_limit.as_secs() + u64::from(cpu_limit.subsec_nanos() > 0);
+
+			set_limit(libc::RLIMIT_CPU, seconds.max(1))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// A directory only the current user can access, removed again once dropped.
+pub(crate) struct PrivateDir {
+	path: PathBuf,
+}
+
+impl PrivateDir {
+	/// Fails rather than reusing anything that already exists at the path.
+	pub fn create() -> io::Result<Self> {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+		let path = env::temp_dir().join(format!(
+			"frick-sandbox-{}-{}",
+			process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed)
+		));
+
+		DirBuilder::new().mode(0o700).create(&path)?;
+
+		Ok(Self { path })
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for PrivateDir {
+	fn drop(&mut self) {
+		_ = fs::remove_dir_all(&self.path);
+	}
+}
+
+/// How a sandboxed program finished, along with everything it wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxOutput {
+	status: ExitStatus,
+	stdout: Vec<u8>,
+	stderr: Vec<u8>,
+	output_truncated: bool,
+}
+
+impl SandboxOutput {
+	#[must_use]
+	pub const fn status(&self) -> ExitStatus {
+		self.status
+	}
+
+	#[must_use]
+	pub fn success(&self) -> bool {
+		self.status.success()
+	}
+
+	/// Returns [`None`] when the program was killed by a signal.
+	#[must_use]
+	pub fn code(&self) -> Option<i32> {
+		self.status.code()
+	}
+
+	#[must_use]
+	pub fn signal(&self) -> Option<i32> {
+		self.status.signal()
+	}
+
+	#[must_use]
+	pub fn stdout(&self) -> &[u8] {
+		&self.stdout
+	}
+
+	#[must_use]
+	pub fn stderr(&self) -> &[u8] {
+		&self.stderr
+	}
+
+	/// Whether the program was killed for going over the output limit.
+	#[must_use]
+	pub const fn output_truncated(&self) -> bool {
+		self.output_truncated
+	}
+
+	const fn with_status(mut self, status: ExitStatus) -> Self {
+		self.status = status;
+		self
+	}
+}
+
+fn read_limited(mut reader: impl Read, limit: usize, pid: pid_t) -> io::Result<(Vec<u8>, bool)> {
+	let mut output = Vec::new();
+
+	reader
+		.by_ref()
+		.take(u64::try_from(limit).unwrap_or(u64::MAX).saturating_add(1))
+		.read_to_end(&mut output)?;
+
+	if output.len() <= limit {
+		return Ok((output, false));
+	}
+
+	output.truncate(limit);
+
+	// the child is not reaped until it is waited on, so the pid can't have been reused
+	unsafe {
+		libc::kill(pid, libc::SIGKILL);
+	}
+
+	Ok((output, true))
+}
+
+#[allow(clippy::unnecessary_cast)]
+fn set_limit(resource: Resource, limit: u64) -> io::Result<()> {
+	let limit = libc::rlimit {
+		rlim_cur: limit as rlim_t,
+		rlim_max: limit as rlim_t,
+	};
+
+	if unsafe { libc::setrlimit(resource, &raw const limit) } < 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,107 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+	io::Error as IoError,
+};
+
+use cranelift_codegen::{CodegenError, settings::SetError};
+use cranelift_module::ModuleError;
+use frick_instructions::BrainInstructionType;
+use frick_interop::{StopReason, Trap};
+
+#[derive(Debug)]
+pub enum CraneliftError {
+	Module(ModuleError),
+	Codegen(CodegenError),
+	Settings(SetError),
+	Io(IoError),
+	UnsupportedHost(&'static str),
+	NotImplemented(BrainInstructionType),
+	NoValueInRegister(usize),
+	NoLoopInfo,
+	NoTapeSnapshot,
+	NeedsStrictSemantics(&'static str),
+	Stopped(StopReason),
+	Trapped(Trap),
+}
+
+impl Display for CraneliftError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Module(..) => f.write_str("an error occurred in the JIT module"),
+			Self::Codegen(..) => f.write_str("an error occurred during code generation"),
+			Self::Settings(..) => f.write_str("invalid code generation setting"),
+			Self::Io(..) => f.write_str("an IO error has occurred"),
+			Self::UnsupportedHost(reason) => {
+				f.write_str("host is not supported by cranelift: ")?;
+				f.write_str(reason)
+			}
+			Self::NotImplemented(i) => {
+				f.write_str("instruction ")?;
+				Debug::fmt(&i, f)?;
+				f.write_str(" is not implemented")
+			}
+			Self::NoValueInRegister(slot) => {
+				f.write_str("no value was found in register ")?;
+				Display::fmt(&slot, f)
+			}
+			Self::NoLoopInfo => f.write_str("no loop info was present when expected"),
+			Self::NoTapeSnapshot => f.write_str("program finished without taking a tape snapshot"),
+			Self::NeedsStrictSemantics(feature) => {
+				f.write_str(feature)?;
+				f.write_str(" needs the program to be compiled with strict semantics")
+			}
+			Self::Stopped(reason) => {
+				f.write_str("program was stopped: ")?;
+				Display::fmt(&reason, f)
+			}
+			Self::Trapped(trap) => {
+				f.write_str("program trapped: ")?;
+				Display::fmt(&trap, f)
+			}
+		}
+	}
+}
+
+impl StdError for CraneliftError {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		match self {
+			Self::Module(e) => Some(e),
+			Self::Codegen(e) => Some(e),
+			Self::Settings(e) => Some(e),
+			Self::Io(e) => Some(e),
+			Self::UnsupportedHost(..)
+			| Self::NotImplemented(..)
+			| Self::NoValueInRegister(..)
+			| Self::NoLoopInfo
+			| Self::NoTapeSnapshot
+			| Self::NeedsStrictSemantics(..)
+			| Self::Stopped(..)
+			| Self::Trapped(..) => None,
+		}
+	}
+}
+
+impl From<ModuleError> for CraneliftError {
+	fn from(value: ModuleError) -> Self {
+		Self::Module(value)
+	}
+}
+
+impl From<CodegenError> for CraneliftError {
+	fn from(value: CodegenError) -> Self {
+		Self::Codegen(value)
+	}
+}
+
+impl From<SetError> for CraneliftError {
+	fn from(value: SetError) -> Self {
+		Self::Settings(value)
+	}
+}
+
+impl From<IoError> for CraneliftError {
+	fn from(value: IoError) -> Self {
+		Self::Io(value)
+	}
+}
@@ -0,0 +1,711 @@
+use cranelift_codegen::{
+	ir::{
+		AbiParam, Block, FuncRef, Function, InstBuilder as _, MemFlags, StackSlot, StackSlotData,
+		StackSlotKind, Type, Value, condcodes::IntCC, types,
+	},
+	isa::TargetFrontendConfig,
+};
+use cranelift_frontend::{FunctionBuilder, Variable};
+use cranelift_jit::JITModule;
+use cranelift_module::{Linkage, Module as _};
+use frick_instructions::{BrainInstruction, BrainInstructionType};
+use frick_spec::{Spec, TapeBoundary};
+use frick_types::{Any, BinaryOperation, Bool, Immediate, Int, RegOrImm, Register, RegisterType};
+use rustc_hash::FxHashMap;
+
+use super::CraneliftError;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeFunctions {
+	getchar: FuncRef,
+	getchar_into: FuncRef,
+	putchar: FuncRef,
+	flush: FuncRef,
+	set_output_threshold: FuncRef,
+	alloc: FuncRef,
+	free: FuncRef,
+	grow_tape: FuncRef,
+	tape_out_of_bounds: FuncRef,
+	cell_overflow: FuncRef,
+	snapshot_tape: FuncRef,
+	start_meter: FuncRef,
+	refuel: FuncRef,
+	finish_meter: FuncRef,
+}
+
+impl RuntimeFunctions {
+	pub fn declare(module: &mut JITModule, func: &mut Function) -> Result<Self, CraneliftError> {
+		let ptr_type = module.target_config().pointer_type();
+
+		// bytes cross the boundary zero extended, as the runtime expects from C callers
+		let byte = AbiParam::new(types::I8).uext();
+		let ptr = AbiParam::new(ptr_type);
+		let i32_param = AbiParam::new(types::I32);
+		let i64_param = AbiParam::new(types::I64);
+
+		let mut declare = |name: &str,
+		                   params: &[AbiParam],
+		                   returns: &[AbiParam]|
+		 -> Result<FuncRef, CraneliftError> {
+			let mut signature = module.make_signature();
+			signature.params.extend_from_slice(params);
+			signature.returns.extend_from_slice(returns);
+
+			let func_id = module.declare_function(name, Linkage::Import, &signature)?;
+
+			Ok(module.declare_func_in_func(func_id, func))
+		};
+
+		Ok(Self {
+			getchar: declare("rust_getchar", &[i32_param], &[i32_param])?,
+			getchar_into: declare("rust_getchar_into", &[ptr, ptr], &[])?,
+			putchar: declare("rust_putchar", &[byte], &[])?,
+			flush: declare("rust_flush", &[], &[])?,
+			set_output_threshold: declare("rust_set_output_threshold", &[ptr], &[])?,
+			alloc: declare("rust_alloc", &[ptr], &[ptr])?,
+			free: declare("rust_free", &[ptr], &[])?,
+			grow_tape: declare("rust_grow_tape", &[ptr, ptr, ptr, ptr], &[])?,
+			tape_out_of_bounds: declare(
+				"rust_tape_out_of_bounds",
+				&[ptr, i32_param, i32_param, ptr],
+				&[],
+			)?,
+			cell_overflow: declare(
+				"rust_cell_overflow",
+				&[ptr, i32_param, i32_param, byte],
+				&[],
+			)?,
+			snapshot_tape: declare("rust_snapshot_tape", &[ptr, ptr, ptr, ptr], &[])?,
+			start_meter: declare("rust_start_meter", &[i64_param, i64_param], &[])?,
+			refuel: declare("rust_refuel", &[], &[i64_param])?,
+			finish_meter: declare("rust_finish_meter", &[], &[i32_param])?,
+		})
+	}
+}
+
+/// Where the tape lives, a growable tape has to be reloaded after every bounds check.
+#[derive(Debug, Clone, Copy)]
+enum Tape {
+	Fixed(Value),
+	Growable { tape: StackSlot, len: StackSlot },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LoopBlocks {
+	header: Block,
+	body: Block,
+	exit: Block,
+}
+
+pub struct InnerAssembler<'a> {
+	builder: FunctionBuilder<'a>,
+	functions: RuntimeFunctions,
+	spec: Spec,
+	config: TargetFrontendConfig,
+	pointer: Variable,
+	fuel: Option<Variable>,
+	tape: Tape,
+	source_name: Option<Value>,
+	exit_block: Block,
+	location: (u32, u32),
+	registers: FxHashMap<usize, Value>,
+	loop_blocks: Vec<LoopBlocks>,
+}
+
+impl<'a> InnerAssembler<'a> {
+	pub fn new(
+		mut builder: FunctionBuilder<'a>,
+		functions: RuntimeFunctions,
+		config: TargetFrontendConfig,
+		spec: Spec,
+		source_name: *const u8,
+	) -> Self {
+		let ptr_type = config.pointer_type();
+
+		let entry_block = builder.create_block();
+		builder.switch_to_block(entry_block);
+
+		let pointer = Variable::from_u32(0);
+		builder.declare_var(pointer, ptr_type);
+		let zero = builder.ins().iconst(ptr_type, 0);
+		builder.def_var(pointer, zero);
+
+		let output_buffer_size = builder
+			.ins()
+			.iconst(ptr_type, spec.output_buffer_size() as i64);
+		builder
+			.ins()
+			.call(functions.set_output_threshold, &[output_buffer_size]);
+
+		let tape_size = builder.ins().iconst(ptr_type, spec.tape_size() as i64);
+		let tape_bytes = builder.ins().iconst(
+			ptr_type,
+			(spec.tape_size() * spec.cell_width().bytes()) as i64,
+		);
+		let alloc_call = builder.ins().call(functions.alloc, &[tape_bytes]);
+		let heap_tape = builder.inst_results(alloc_call)[0];
+
+		let zero_byte = builder.ins().iconst(types::I8, 0);
+		builder.call_memset(config, heap_tape, zero_byte, tape_bytes);
+
+		let tape = match spec.boundary() {
+			TapeBoundary::Grow => {
+				let slot_size = ptr_type.bytes();
+				let align_shift = slot_size.trailing_zeros() as u8;
+
+				let tape = builder.create_sized_stack_slot(StackSlotData::new(
+					StackSlotKind::ExplicitSlot,
+					slot_size,
+					align_shift,
+				));
+				let len = builder.create_sized_stack_slot(StackSlotData::new(
+					StackSlotKind::ExplicitSlot,
+					slot_size,
+					align_shift,
+				));
+
+				builder.ins().stack_store(heap_tape, tape, 0);
+				builder.ins().stack_store(tape_size, len, 0);
+
+				Tape::Growable { tape, len }
+			}
+			TapeBoundary::Wrap | TapeBoundary::Abort => Tape::Fixed(heap_tape),
+		};
+
+		let fuel = spec.is_metered().then(|| {
+			let fuel = Variable::from_u32(1);
+			builder.declare_var(fuel, types::I64);
+
+			// starting empty makes the first iteration check in with the runtime
+			let empty = builder.ins().iconst(types::I64, 0);
+			builder.def_var(fuel, empty);
+
+			let step_limit = spec.step_limit().unwrap_or(u64::MAX);
+			// u64::MAX is reserved for no timeout
+			let timeout_millis = spec.timeout().map_or(u64::MAX, |timeout| {
+				u64::try_from(timeout.as_millis())
+					.map_or(u64::MAX - 1, |millis| millis.min(u64::MAX - 1))
+			});
+
+			let step_limit = builder.ins().iconst(types::I64, step_limit as i64);
+			let timeout_millis = builder.ins().iconst(types::I64, timeout_millis as i64);
+			builder
+				.ins()
+				.call(functions.start_meter, &[step_limit, timeout_millis]);
+
+			fuel
+		});
+
+		// only needed for out of bounds and overflow messages, so keep it out of programs that can't
+		// fail either way
+		let source_name = match spec.boundary() {
+			TapeBoundary::Wrap if !spec.overflow().is_trapping() => None,
+			_ => Some(builder.ins().iconst(ptr_type, source_name as i64)),
+		};
+
+		let exit_block = builder.create_block();
+
+		Self {
+			builder,
+			functions,
+			spec,
+			config,
+			pointer,
+			fuel,
+			tape,
+			source_name,
+			exit_block,
+			location: (0, 0),
+			registers: FxHashMap::default(),
+			loop_blocks: Vec::new(),
+		}
+	}
+
+	pub fn assemble(
+		mut self,
+		instrs: &[BrainInstruction],
+		file_data: &str,
+	) -> Result<(), CraneliftError> {
+		let line_positions = line_numbers::LinePositions::from(file_data);
+
+		for i in instrs {
+			let i_range = i.span();
+
+			let line_span = line_positions.from_region(i_range.start, i_range.end)[0];
+			self.location = (
+				(line_span.line.as_usize() + 1) as u32,
+				line_span.start_col + 1,
+			);
+
+			self.compile_instruction(i.instr())?;
+		}
+
+		self.builder.ins().jump(self.exit_block, &[]);
+		self.builder.switch_to_block(self.exit_block);
+
+		self.finish();
+
+		Ok(())
+	}
+
+	fn finish(mut self) {
+		let ptr_type = self.config.pointer_type();
+
+		let (tape, tape_len) = match self.tape {
+			Tape::Fixed(tape) => (
+				tape,
+				self.builder
+					.ins()
+					.iconst(ptr_type, self.spec.tape_size() as i64),
+			),
+			Tape::Growable { tape, len } => (
+				self.builder.ins().stack_load(ptr_type, tape, 0),
+				self.builder.ins().stack_load(ptr_type, len, 0),
+			),
+		};
+		let pointer = self.builder.use_var(self.pointer);
+		let cell_bytes = self.cell_bytes();
+
+		self.builder.ins().call(
+			self.functions.snapshot_tape,
+			&[tape, tape_len, pointer, cell_bytes],
+		);
+		self.builder.ins().call(self.functions.free, &[tape]);
+		self.builder.ins().call(self.functions.flush, &[]);
+
+		let exit_code = if self.fuel.is_some() {
+			let finish_call = self.builder.ins().call(self.functions.finish_meter, &[]);
+
+			self.builder.inst_results(finish_call)[0]
+		} else {
+			self.builder.ins().iconst(types::I32, 0)
+		};
+
+		self.builder.ins().return_(&[exit_code]);
+
+		self.builder.seal_all_blocks();
+		self.builder.finalize();
+	}
+
+	#[allow(deprecated)]
+	fn compile_instruction(&mut self, instr: BrainInstructionType) -> Result<(), CraneliftError> {
+		match instr {
+			BrainInstructionType::LoadCellIntoRegister {
+				pointer_reg,
+				output_reg,
+			} => {
+				let ptr = self.value_at(pointer_reg)?;
+				let cell_type = self.cell_type();
+				let cell = self
+					.builder
+					.ins()
+					.load(cell_type, MemFlags::trusted(), ptr, 0);
+
+				self.set_value_at(output_reg, cell);
+			}
+			BrainInstructionType::StoreValueIntoCell { value, pointer_reg } => {
+				let ptr = self.value_at(pointer_reg)?;
+				let value = self.resolve_value(value)?;
+
+				self.builder.ins().store(MemFlags::trusted(), value, ptr, 0);
+			}
+			BrainInstructionType::StoreImmediateIntoRegister { imm, output_reg } => {
+				let value = self.immediate(imm);
+
+				self.set_value_at(output_reg, value);
+			}
+			BrainInstructionType::LoadTapePointerIntoRegister { output_reg } => {
+				let pointer = self.builder.use_var(self.pointer);
+
+				self.set_value_at(output_reg, pointer);
+			}
+			BrainInstructionType::StoreRegisterIntoTapePointer { input_reg } => {
+				let value = self.value_at(input_reg)?;
+
+				self.builder.def_var(self.pointer, value);
+			}
+			BrainInstructionType::CalculateTapeOffset {
+				tape_pointer_reg,
+				output_reg,
+			} => {
+				let pointer = self.value_at(tape_pointer_reg)?;
+				let tape = self.tape_base();
+
+				let byte_offset = match self.spec.cell_width().bytes() {
+					1 => pointer,
+					bytes => self
+						.builder
+						.ins()
+						.ishl_imm(pointer, i64::from(bytes.trailing_zeros())),
+				};
+
+				let cell_ptr = self.builder.ins().iadd(tape, byte_offset);
+
+				self.set_value_at(output_reg, cell_ptr);
+			}
+			BrainInstructionType::CheckTapeBounds { tape_pointer_reg } => {
+				self.check_tape_bounds(tape_pointer_reg)?;
+			}
+			BrainInstructionType::CheckCellOverflow {
+				cell_reg,
+				result_reg,
+				op,
+			} => self.check_cell_overflow(cell_reg, result_reg, op)?,
+			BrainInstructionType::PerformBinaryValueOperation {
+				lhs,
+				rhs,
+				output_reg,
+				op,
+			} => {
+				let lhs = self.resolve_value(lhs)?;
+				let rhs = self.resolve_value(rhs)?;
+
+				let value = match op {
+					BinaryOperation::Add => self.builder.ins().iadd(lhs, rhs),
+					BinaryOperation::Sub => self.builder.ins().isub(lhs, rhs),
+					BinaryOperation::Mul => self.builder.ins().imul(lhs, rhs),
+					BinaryOperation::BitwiseAnd => self.builder.ins().band(lhs, rhs),
+					BinaryOperation::BitwiseShl => self.builder.ins().ishl(lhs, rhs),
+					_ => return Err(CraneliftError::NotImplemented(instr)),
+				};
+
+				self.set_value_at(output_reg, value);
+			}
+			BrainInstructionType::DuplicateRegister {
+				input_reg,
+				output_reg,
+			} => {
+				let value = self.value_at::<Any>(input_reg)?;
+
+				self.set_value_at(output_reg, value);
+			}
+			BrainInstructionType::InputIntoRegister { output_reg } => {
+				// unchanged EOF is lowered to `InputIntoCell`, so any value works here
+				let eof_value = self.builder.ins().iconst(
+					types::I32,
+					i64::from(self.spec.eof_value().unwrap_or_default()),
+				);
+
+				let call = self
+					.builder
+					.ins()
+					.call(self.functions.getchar, &[eof_value]);
+				let value = self.builder.inst_results(call)[0];
+
+				let cell_type = self.cell_type();
+				let value = if cell_type == types::I32 {
+					value
+				} else {
+					self.builder.ins().ireduce(cell_type, value)
+				};
+
+				self.set_value_at(output_reg, value);
+			}
+			BrainInstructionType::InputIntoCell { pointer_reg } => {
+				let ptr = self.value_at(pointer_reg)?;
+				let cell_bytes = self.cell_bytes();
+
+				self.builder
+					.ins()
+					.call(self.functions.getchar_into, &[ptr, cell_bytes]);
+			}
+			BrainInstructionType::OutputFromRegister { input_reg } => {
+				let value = self.value_at(input_reg)?;
+
+				// only the low byte of a cell is ever output
+				let value = if self.cell_type() == types::I8 {
+					value
+				} else {
+					self.builder.ins().ireduce(types::I8, value)
+				};
+
+				self.builder.ins().call(self.functions.putchar, &[value]);
+			}
+			BrainInstructionType::StartLoop => {
+				let loop_blocks = LoopBlocks {
+					header: self.builder.create_block(),
+					body: self.builder.create_block(),
+					exit: self.builder.create_block(),
+				};
+
+				self.loop_blocks.push(loop_blocks);
+
+				self.builder.ins().jump(loop_blocks.header, &[]);
+				self.builder.switch_to_block(loop_blocks.header);
+			}
+			BrainInstructionType::EndLoop => {
+				let loop_blocks = self.loop_blocks.pop().ok_or(CraneliftError::NoLoopInfo)?;
+
+				self.builder.switch_to_block(loop_blocks.exit);
+			}
+			BrainInstructionType::CompareValues {
+				lhs,
+				rhs,
+				output_reg,
+			} => {
+				let lhs = self.resolve_value(lhs)?;
+				let rhs = self.resolve_value(rhs)?;
+
+				let value = self.builder.ins().icmp(IntCC::Equal, lhs, rhs);
+
+				self.set_value_at(output_reg, value);
+			}
+			BrainInstructionType::JumpIf { input_reg } => {
+				let loop_blocks = self.last_loop_blocks()?;
+				let condition = self.value_at::<Bool>(input_reg)?;
+
+				self.builder
+					.ins()
+					.brif(condition, loop_blocks.exit, &[], loop_blocks.body, &[]);
+				self.builder.switch_to_block(loop_blocks.body);
+			}
+			BrainInstructionType::JumpToHeader => {
+				let loop_blocks = self.last_loop_blocks()?;
+
+				if let Some(fuel) = self.fuel {
+					self.burn_fuel(fuel);
+				}
+
+				self.builder.ins().jump(loop_blocks.header, &[]);
+			}
+			instr => return Err(CraneliftError::NotImplemented(instr)),
+		}
+
+		Ok(())
+	}
+
+	fn check_tape_bounds(&mut self, input_reg: Register<Int>) -> Result<(), CraneliftError> {
+		let ptr_type = self.config.pointer_type();
+
+		let pointer = self.value_at(input_reg)?;
+
+		let out_of_bounds_block = self.builder.create_block();
+		let in_bounds_block = self.builder.create_block();
+
+		match self.tape {
+			Tape::Growable { tape, len } => {
+				let check_len_block = self.builder.create_block();
+				let grow_block = self.builder.create_block();
+
+				// the pointer is unsigned, so moving left of the first cell shows up as negative
+				let is_negative = self
+					.builder
+					.ins()
+					.icmp_imm(IntCC::SignedLessThan, pointer, 0);
+				self.builder.ins().brif(
+					is_negative,
+					out_of_bounds_block,
+					&[],
+					check_len_block,
+					&[],
+				);
+
+				self.builder.switch_to_block(check_len_block);
+
+				let tape_len = self.builder.ins().stack_load(ptr_type, len, 0);
+				let needs_growth =
+					self.builder
+						.ins()
+						.icmp(IntCC::UnsignedGreaterThanOrEqual, pointer, tape_len);
+				self.builder
+					.ins()
+					.brif(needs_growth, grow_block, &[], in_bounds_block, &[]);
+
+				self.builder.switch_to_block(grow_block);
+
+				let tape_addr = self.builder.ins().stack_addr(ptr_type, tape, 0);
+				let len_addr = self.builder.ins().stack_addr(ptr_type, len, 0);
+				let cell_bytes = self.cell_bytes();
+				self.builder.ins().call(
+					self.functions.grow_tape,
+					&[tape_addr, len_addr, pointer, cell_bytes],
+				);
+				self.builder.ins().jump(in_bounds_block, &[]);
+			}
+			Tape::Fixed(..) => {
+				let is_out_of_bounds = self.builder.ins().icmp_imm(
+					IntCC::UnsignedGreaterThanOrEqual,
+					pointer,
+					self.spec.tape_size() as i64,
+				);
+				self.builder.ins().brif(
+					is_out_of_bounds,
+					out_of_bounds_block,
+					&[],
+					in_bounds_block,
+					&[],
+				);
+			}
+		}
+
+		self.builder.switch_to_block(out_of_bounds_block);
+		self.builder.set_cold_block(out_of_bounds_block);
+
+		let (line, column) = self.location;
+
+		let source_name = match self.source_name {
+			Some(source_name) => source_name,
+			None => self.builder.ins().iconst(ptr_type, 0),
+		};
+		let line = self.builder.ins().iconst(types::I32, i64::from(line));
+		let column = self.builder.ins().iconst(types::I32, i64::from(column));
+
+		self.builder.ins().call(
+			self.functions.tape_out_of_bounds,
+			&[source_name, line, column, pointer],
+		);
+		// only reached when the program runs hosted, otherwise the runtime exits the process
+		self.builder.ins().jump(self.exit_block, &[]);
+
+		self.builder.switch_to_block(in_bounds_block);
+
+		Ok(())
+	}
+
+	fn check_cell_overflow(
+		&mut self,
+		cell_reg: Register<Int>,
+		result_reg: Register<Int>,
+		op: BinaryOperation,
+	) -> Result<(), CraneliftError> {
+		let ptr_type = self.config.pointer_type();
+
+		let cell = self.value_at(cell_reg)?;
+		let result = self.value_at(result_reg)?;
+
+		// the step is never zero, so wrapping always lands on the wrong side of the old value
+		let (cond, underflow) = match op {
+			BinaryOperation::Add => (IntCC::UnsignedLessThan, false),
+			BinaryOperation::Sub => (IntCC::UnsignedGreaterThan, true),
+			op => {
+				return Err(CraneliftError::NotImplemented(
+					BrainInstructionType::CheckCellOverflow {
+						cell_reg,
+						result_reg,
+						op,
+					},
+				));
+			}
+		};
+
+		let overflowed = self.builder.ins().icmp(cond, result, cell);
+
+		let overflow_block = self.builder.create_block();
+		let continue_block = self.builder.create_block();
+
+		self.builder
+			.ins()
+			.brif(overflowed, overflow_block, &[], continue_block, &[]);
+
+		self.builder.switch_to_block(overflow_block);
+		self.builder.set_cold_block(overflow_block);
+
+		let (line, column) = self.location;
+
+		let source_name = match self.source_name {
+			Some(source_name) => source_name,
+			None => self.builder.ins().iconst(ptr_type, 0),
+		};
+		let line = self.builder.ins().iconst(types::I32, i64::from(line));
+		let column = self.builder.ins().iconst(types::I32, i64::from(column));
+		let underflow = self.builder.ins().iconst(types::I8, i64::from(underflow));
+
+		self.builder.ins().call(
+			self.functions.cell_overflow,
+			&[source_name, line, column, underflow],
+		);
+		// only reached when the program runs hosted, otherwise the runtime exits the process
+		self.builder.ins().jump(self.exit_block, &[]);
+
+		self.builder.switch_to_block(continue_block);
+
+		Ok(())
+	}
+
+	fn burn_fuel(&mut self, fuel: Variable) {
+		let refuel_block = self.builder.create_block();
+		let burn_block = self.builder.create_block();
+
+		self.builder.set_cold_block(refuel_block);
+
+		let fuel_value = self.builder.use_var(fuel);
+		self.builder
+			.ins()
+			.brif(fuel_value, burn_block, &[], refuel_block, &[]);
+
+		self.builder.switch_to_block(refuel_block);
+
+		let refuel_call = self.builder.ins().call(self.functions.refuel, &[]);
+		let refueled = self.builder.inst_results(refuel_call)[0];
+		self.builder.def_var(fuel, refueled);
+
+		self.builder
+			.ins()
+			.brif(refueled, burn_block, &[], self.exit_block, &[]);
+
+		self.builder.switch_to_block(burn_block);
+
+		let available = self.builder.use_var(fuel);
+		let remaining = self.builder.ins().iadd_imm(available, -1);
+		self.builder.def_var(fuel, remaining);
+	}
+
+	fn tape_base(&mut self) -> Value {
+		match self.tape {
+			Tape::Fixed(tape) => tape,
+			Tape::Growable { tape, .. } => {
+				self.builder
+					.ins()
+					.stack_load(self.config.pointer_type(), tape, 0)
+			}
+		}
+	}
+
+	fn cell_type(&self) -> Type {
+		Type::int(self.spec.cell_width().bits() as u16).unwrap_or(types::I8)
+	}
+
+	fn cell_bytes(&mut self) -> Value {
+		self.builder.ins().iconst(
+			self.config.pointer_type(),
+			self.spec.cell_width().bytes() as i64,
+		)
+	}
+
+	fn immediate(&mut self, imm: Immediate) -> Value {
+		let ty = Type::int(imm.size() as u16).unwrap_or(types::I64);
+
+		self.builder.ins().iconst(ty, imm.value() as i64)
+	}
+
+	fn resolve_value(&mut self, reg_or_imm: RegOrImm<Int>) -> Result<Value, CraneliftError> {
+		match reg_or_imm {
+			RegOrImm::Reg(r) => self.value_at(r),
+			RegOrImm::Imm(i) => Ok(self.immediate(i)),
+		}
+	}
+
+	fn last_loop_blocks(&self) -> Result<LoopBlocks, CraneliftError> {
+		self.loop_blocks
+			.last()
+			.copied()
+			.ok_or(CraneliftError::NoLoopInfo)
+	}
+
+	fn value_at<T>(&self, reg: Register<T>) -> Result<Value, CraneliftError>
+	where
+		T: ?Sized + RegisterType,
+	{
+		self.registers
+			.get(&reg.index())
+			.copied()
+			.ok_or_else(|| CraneliftError::NoValueInRegister(reg.index()))
+	}
+
+	fn set_value_at<T>(&mut self, reg: Register<T>, value: Value)
+	where
+		T: ?Sized + RegisterType,
+	{
+		self.registers.insert(reg.index(), value);
+	}
+}
@@ -0,0 +1,146 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod error;
+mod inner;
+mod module;
+
+use std::{ffi::CString, mem::ManuallyDrop, path::PathBuf};
+
+use cranelift_codegen::{
+	ir::{AbiParam, types},
+	settings::{self, Configurable as _},
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{Linkage, Module as _};
+use frick_instructions::BrainInstruction;
+pub use frick_interop::{SourceLocation, StopReason, TapeSnapshot, Trap};
+use frick_spec::Spec;
+use tracing::info;
+
+use self::inner::{InnerAssembler, RuntimeFunctions};
+pub use self::{error::*, module::*};
+
+/// Compiles instructions to native code with Cranelift, trading the LLVM backend's
+/// optimizations for much faster compile times.
+pub struct Assembler {
+	file_path: PathBuf,
+	file_data: String,
+	spec: Spec,
+}
+
+impl Assembler {
+	#[must_use]
+	pub const fn new(file_path: PathBuf, file_data: String) -> Self {
+		Self {
+			file_path,
+			file_data,
+			spec: Spec::new(),
+		}
+	}
+
+	#[must_use]
+	pub const fn with_spec(mut self, spec: Spec) -> Self {
+		self.spec = spec;
+		self
+	}
+
+	#[tracing::instrument(skip_all)]
+	pub fn assemble(&self, instrs: &[BrainInstruction]) -> Result<AssembledModule, CraneliftError> {
+		let mut flags = settings::builder();
+		flags.set("is_pic", "false")?;
+		flags.set("opt_level", "speed")?;
+		flags.set(
+			"enable_verifier",
+			if cfg!(debug_assertions) {
+				"true"
+			} else {
+				"false"
+			},
+		)?;
+
+		let isa = cranelift_native::builder()
+			.map_err(CraneliftError::UnsupportedHost)?
+			.finish(settings::Flags::new(flags))?;
+
+		let mut jit_builder = JITBuilder::with_isa(isa, cranelift_module::default_libcall_names());
+
+		info!("adding runtime functions to the JIT");
+		jit_builder
+			.symbol("rust_getchar", frick_interop::rust_getchar as *const u8)
+			.symbol(
+				"rust_getchar_into",
+				frick_interop::rust_getchar_into as *const u8,
+			)
+			.symbol("rust_putchar", frick_interop::rust_putchar as *const u8)
+			.symbol("rust_flush", frick_interop::rust_flush as *const u8)
+			.symbol(
+				"rust_set_output_threshold",
+				frick_interop::rust_set_output_threshold as *const u8,
+			)
+			.symbol("rust_alloc", frick_interop::rust_alloc as *const u8)
+			.symbol("rust_free", frick_interop::rust_free as *const u8)
+			.symbol("rust_grow_tape", frick_interop::rust_grow_tape as *const u8)
+			.symbol(
+				"rust_tape_out_of_bounds",
+				frick_interop::rust_tape_out_of_bounds as *const u8,
+			)
+			.symbol(
+				"rust_cell_overflow",
+				frick_interop::rust_cell_overflow as *const u8,
+			)
+			.symbol(
+				"rust_snapshot_tape",
+				frick_interop::rust_snapshot_tape as *const u8,
+			)
+			.symbol(
+				"rust_start_meter",
+				frick_interop::rust_start_meter as *const u8,
+			)
+			.symbol("rust_refuel", frick_interop::rust_refuel as *const u8)
+			.symbol(
+				"rust_finish_meter",
+				frick_interop::rust_finish_meter as *const u8,
+			);
+
+		let mut module = JITModule::new(jit_builder);
+
+		let source_name = CString::new(self.file_path.display().to_string())
+			.unwrap_or_else(|_| c"<unknown>".to_owned());
+
+		let mut ctx = module.make_context();
+		ctx.func.signature.returns.push(AbiParam::new(types::I32));
+
+		let main = module.declare_function("main", Linkage::Export, &ctx.func.signature)?;
+
+		let functions = RuntimeFunctions::declare(&mut module, &mut ctx.func)?;
+
+		info!("lowering instructions");
+		let mut builder_ctx = FunctionBuilderContext::new();
+		let builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+
+		InnerAssembler::new(
+			builder,
+			functions,
+			module.target_config(),
+			self.spec,
+			source_name.as_ptr().cast(),
+		)
+		.assemble(instrs, &self.file_data)?;
+
+		info!("compiling main");
+		module.define_function(main, &mut ctx)?;
+		module.clear_context(&mut ctx);
+		module.finalize_definitions()?;
+
+		let main = module.get_finalized_function(main);
+
+		Ok(AssembledModule {
+			module: ManuallyDrop::new(module),
+			// main was declared as taking nothing and returning an i32
+			main: unsafe { std::mem::transmute::<*const u8, extern "C" fn() -> i32>(main) },
+			strict_semantics: self.spec.strict_semantics(),
+			_source_name: source_name,
+		})
+	}
+}
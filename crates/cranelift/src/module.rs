@@ -0,0 +1,171 @@
+use std::{
+	ffi::CString,
+	io::{Read, Write},
+	mem::ManuallyDrop,
+};
+
+use cranelift_jit::JITModule;
+use frick_interop::TapeSnapshot;
+
+use super::CraneliftError;
+
+pub struct AssembledModule {
+	pub(crate) module: ManuallyDrop<JITModule>,
+	pub(crate) main: extern "C" fn() -> i32,
+	pub(crate) strict_semantics: bool,
+	/// Referenced by the generated code for out of bounds messages.
+	pub(crate) _source_name: CString,
+}
+
+impl AssembledModule {
+	/// Calls the finalized `main` directly on this thread.
+	///
+	/// A step limit or timeout in the [`Spec`](frick_spec::Spec) the module was assembled with
+	/// stops it early with [`CraneliftError::Stopped`].
+	pub fn execute(&self) -> Result<(), CraneliftError> {
+		let ((_, stopped), trap) =
+			frick_interop::with_trap(|| frick_interop::with_meter(|| (self.main)()));
+
+		if let Some(trap) = trap {
+			return Err(CraneliftError::Trapped(trap));
+		}
+
+		match stopped {
+			Some(reason) => Err(CraneliftError::Stopped(reason)),
+			None => Ok(()),
+		}
+	}
+
+	/// Like [`Self::execute`], with the runtime's `rust_getchar` and `rust_putchar` going to
+	/// `reader` and `writer` instead of stdin and stdout.
+	///
+	/// Failures of either come back as [`CraneliftError::Io`]. The generated code returns from
+	/// `main` after a trap here, which comes back as [`CraneliftError::Trapped`] instead of
+	/// exiting the process.
+	pub fn execute_with(
+		&self,
+		reader: &mut impl Read,
+		writer: &mut impl Write,
+	) -> Result<(), CraneliftError> {
+		frick_interop::with_io(reader, writer, || self.execute())?
+	}
+
+	/// Like [`Self::execute`], returning the tape `main` hands to `rust_snapshot_tape` right
+	/// before freeing it.
+	///
+	/// That tape only reflects the whole program when it was assembled with strict semantics,
+	/// so modules assembled without return [`CraneliftError::NeedsStrictSemantics`].
+	pub fn execute_snapshot(&self) -> Result<TapeSnapshot, CraneliftError> {
+		if !self.strict_semantics {
+			return Err(CraneliftError::NeedsStrictSemantics("a tape snapshot"));
+		}
+
+		let (result, snapshot) = frick_interop::with_snapshot(|| self.execute());
+
+		result?;
+
+		snapshot.ok_or(CraneliftError::NoTapeSnapshot)
+	}
+
+	/// Like [`Self::execute_snapshot`], with I/O handled as in [`Self::execute_with`].
+	pub fn execute_snapshot_with(
+		&self,
+		reader: &mut impl Read,
+		writer: &mut impl Write,
+	) -> Result<TapeSnapshot, CraneliftError> {
+		frick_interop::with_io(reader, writer, || self.execute_snapshot())?
+	}
+}
+
+impl Drop for AssembledModule {
+	fn drop(&mut self) {
+		// nothing can call into the code once the module is gone
+		unsafe {
+			ManuallyDrop::take(&mut self.module).free_memory();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{io, path::PathBuf};
+
+	use frick_interop::{
+		TapeSnapshot, Trap,
+		test_util::{self, Backend},
+	};
+	use frick_optimizer::Optimizer;
+	use frick_serialize::EmitOptions;
+	use frick_spec::Spec;
+
+	use crate::{AssembledModule, Assembler, CraneliftError};
+
+	struct Cranelift;
+
+	impl Cranelift {
+		fn assemble(program: &str, spec: Spec) -> Result<AssembledModule, CraneliftError> {
+			let ops = frick_operations::parse_str(program).unwrap();
+			let instrs = Optimizer::run(ops, spec, EmitOptions::none(), &mut Vec::new()).unwrap();
+
+			Assembler::new(PathBuf::from("test.bf"), program.to_owned())
+				.with_spec(spec)
+				.assemble(&instrs)
+		}
+	}
+
+	impl Backend for Cranelift {
+		type Error = CraneliftError;
+
+		fn execute(program: &str, spec: Spec) -> Result<(), Self::Error> {
+			Self::assemble(program, spec)?.execute()
+		}
+
+		fn execute_with(
+			program: &str,
+			spec: Spec,
+			output: &mut Vec<u8>,
+		) -> Result<(), Self::Error> {
+			Self::assemble(program, spec)?.execute_with(&mut io::empty(), output)
+		}
+
+		fn execute_snapshot_with(
+			program: &str,
+			spec: Spec,
+			output: &mut Vec<u8>,
+		) -> Result<TapeSnapshot, Self::Error> {
+			Self::assemble(program, spec)?.execute_snapshot_with(&mut io::empty(), output)
+		}
+
+		fn trap(error: Self::Error) -> Option<Trap> {
+			match error {
+				CraneliftError::Trapped(trap) => Some(trap),
+				_ => None,
+			}
+		}
+	}
+
+	#[test]
+	fn snapshots() {
+		test_util::check_snapshots::<Cranelift>();
+	}
+
+	#[test]
+	fn snapshot_needs_strict_semantics() {
+		assert!(matches!(
+			Cranelift::execute_snapshot_with("+++>++", Spec::new(), &mut Vec::new()),
+			Err(CraneliftError::NeedsStrictSemantics(..))
+		));
+	}
+
+	#[test]
+	fn standalone_overflow_exits() {
+		test_util::check_standalone_overflow::<Cranelift>(
+			"module::tests::standalone_overflow_exits",
+		);
+	}
+
+	#[test]
+	fn hosted_traps_are_returned() {
+		test_util::check_hosted_traps::<Cranelift>();
+	}
+}
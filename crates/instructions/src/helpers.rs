@@ -1,5 +1,6 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 
+use frick_spec::{Spec, TapeBoundary};
 use frick_types::{BinaryOperation, Immediate, Int, Pointer, RegOrImm, Register};
 use frick_utils::Convert as _;
 
@@ -18,6 +19,7 @@ impl LoadCellInformation {
 		offset: i32,
 		instr_offset: usize,
 		tape_pointer_reg: Option<Register<Int>>,
+		spec: Spec,
 	) -> (Self, Vec<BrainInstructionType>) {
 		let mut instrs = Vec::new();
 
@@ -65,25 +67,27 @@ impl LoadCellInformation {
 					let new_tape_pointer_reg = Register::new(instr_offset + 3);
 					let pointer_reg = Register::new(instr_offset + 4);
 					let cell_reg = Register::new(instr_offset + 5);
-					instrs.extend([
-						BrainInstructionType::PerformBinaryValueOperation {
-							lhs: RegOrImm::Reg(tape_pointer_reg),
-							rhs: RegOrImm::Imm(Immediate::pointer(
-								offset.unsigned_abs().convert::<u64>(),
-							)),
-							output_reg: Register::new(instr_offset),
-							op: if offset.is_positive() {
-								BinaryOperation::Add
-							} else {
-								BinaryOperation::Sub
-							},
-						},
-						BrainInstructionType::PerformBinaryValueOperation {
-							lhs: RegOrImm::Reg(Register::new(instr_offset)),
-							rhs: RegOrImm::Imm(Immediate::TAPE_SIZE_MINUS_ONE),
-							output_reg: new_tape_pointer_reg,
-							op: BinaryOperation::BitwiseAnd,
+
+					instrs.push(BrainInstructionType::PerformBinaryValueOperation {
+						lhs: RegOrImm::Reg(tape_pointer_reg),
+						rhs: RegOrImm::Imm(Immediate::pointer(
+							offset.unsigned_abs().convert::<u64>(),
+						)),
+						output_reg: Register::new(instr_offset),
+						op: if offset.is_positive() {
+							BinaryOperation::Add
+						} else {
+							BinaryOperation::Sub
 						},
+					});
+
+					instrs.extend(bound_tape_pointer(
+						Register::new(instr_offset),
+						new_tape_pointer_reg,
+						spec,
+					));
+
+					instrs.extend([
 						BrainInstructionType::CalculateTapeOffset {
 							tape_pointer_reg: new_tape_pointer_reg,
 							output_reg: pointer_reg,
@@ -122,12 +126,15 @@ impl LoadCellInformation {
 								BinaryOperation::Sub
 							},
 						},
-						BrainInstructionType::PerformBinaryValueOperation {
-							lhs: RegOrImm::Reg(Register::new(instr_offset + 2)),
-							rhs: RegOrImm::Imm(Immediate::TAPE_SIZE_MINUS_ONE),
-							output_reg: tape_pointer_reg,
-							op: BinaryOperation::BitwiseAnd,
-						},
+					]);
+
+					instrs.extend(bound_tape_pointer(
+						Register::new(instr_offset + 2),
+						tape_pointer_reg,
+						spec,
+					));
+
+					instrs.extend([
 						BrainInstructionType::CalculateTapeOffset {
 							tape_pointer_reg,
 							output_reg: pointer_reg,
@@ -153,3 +160,42 @@ impl LoadCellInformation {
 		)
 	}
 }
+
+pub fn bound_tape_pointer(
+	input_reg: Register<Int>,
+	output_reg: Register<Int>,
+	spec: Spec,
+) -> Vec<BrainInstructionType> {
+	match spec.boundary() {
+		TapeBoundary::Wrap => vec![BrainInstructionType::PerformBinaryValueOperation {
+			lhs: RegOrImm::Reg(input_reg),
+			rhs: RegOrImm::Imm(Immediate::tape_size_minus_one(spec)),
+			output_reg,
+			op: BinaryOperation::BitwiseAnd,
+		}],
+		TapeBoundary::Abort | TapeBoundary::Grow => vec![
+			BrainInstructionType::DuplicateRegister {
+				input_reg: input_reg.cast(),
+				output_reg: output_reg.cast(),
+			},
+			BrainInstructionType::CheckTapeBounds {
+				tape_pointer_reg: output_reg,
+			},
+		],
+	}
+}
+
+pub fn check_cell_overflow(
+	cell_reg: Register<Int>,
+	result_reg: Register<Int>,
+	op: BinaryOperation,
+	spec: Spec,
+) -> Option<BrainInstructionType> {
+	spec.overflow()
+		.is_trapping()
+		.then_some(BrainInstructionType::CheckCellOverflow {
+			cell_reg,
+			result_reg,
+			op,
+		})
+}
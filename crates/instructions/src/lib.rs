@@ -9,11 +9,12 @@ use alloc::{vec, vec::Vec};
 use core::ops::{Deref, DerefMut, Range};
 
 use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::{EofMode, Spec};
 use frick_types::{Any, BinaryOperation, Bool, Immediate, Int, Pointer, RegOrImm, Register};
 use frick_utils::Convert as _;
 use serde::{Deserialize, Serialize};
 
-use self::helpers::LoadCellInformation;
+use self::helpers::{LoadCellInformation, bound_tape_pointer, check_cell_overflow};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -89,6 +90,16 @@ pub enum BrainInstructionType {
 		tape_pointer_reg: Register<Int>,
 		output_reg: Register<Pointer>,
 	},
+	CheckTapeBounds {
+		tape_pointer_reg: Register<Int>,
+	},
+	/// Exits with an error if `op` went past either end of the cell's range going from
+	/// `cell_reg` to `result_reg`.
+	CheckCellOverflow {
+		cell_reg: Register<Int>,
+		result_reg: Register<Int>,
+		op: BinaryOperation,
+	},
 	#[deprecated]
 	PerformBinaryRegisterOperation {
 		lhs_reg: Register<Int>,
@@ -109,6 +120,10 @@ pub enum BrainInstructionType {
 	InputIntoRegister {
 		output_reg: Register<Int>,
 	},
+	/// Reads input straight into the cell, leaving it unchanged on EOF.
+	InputIntoCell {
+		pointer_reg: Register<Pointer>,
+	},
 	OutputFromRegister {
 		input_reg: Register<Int>,
 	},
@@ -157,6 +172,9 @@ impl BrainInstructionType {
 				output_reg: int_reg,
 			}
 			| Self::StoreRegisterIntoTapePointer { input_reg: int_reg }
+			| Self::CheckTapeBounds {
+				tape_pointer_reg: int_reg,
+			}
 			| Self::InputIntoRegister {
 				output_reg: int_reg,
 			}
@@ -172,6 +190,12 @@ impl BrainInstructionType {
 				rhs_reg,
 				output_reg,
 			} => lhs_reg == reg || rhs_reg == reg || output_reg == reg,
+			Self::CheckCellOverflow {
+				cell_reg,
+				result_reg,
+				..
+			} => cell_reg == reg || result_reg == reg,
+			Self::InputIntoCell { pointer_reg } => pointer_reg == reg,
 			Self::JumpIf { input_reg } => input_reg == reg,
 			_ => false,
 		}
@@ -179,27 +203,34 @@ impl BrainInstructionType {
 }
 
 pub trait ToInstructions {
-	fn to_instructions(&self) -> Vec<BrainInstruction>;
+	fn to_instructions(&self, spec: Spec) -> Vec<BrainInstruction>;
 }
 
 impl ToInstructions for BrainOperation {
-	fn to_instructions(&self) -> Vec<BrainInstruction> {
+	fn to_instructions(&self, spec: Spec) -> Vec<BrainInstruction> {
 		match self.op() {
 			&BrainOperationType::IncrementCell(CellOffsetOptions { value, offset }) => {
-				let (load_cell_info, mut instrs) = LoadCellInformation::create(offset, 0, None);
-
-				instrs.extend([
-					BrainInstructionType::PerformBinaryValueOperation {
-						lhs: RegOrImm::Reg(load_cell_info.cell_reg),
-						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
-						output_reg: Register::new(load_cell_info.instr_offset),
-						op: BinaryOperation::Add,
-					},
-					BrainInstructionType::StoreValueIntoCell {
-						value: RegOrImm::Reg(Register::new(load_cell_info.instr_offset)),
-						pointer_reg: load_cell_info.pointer_reg,
-					},
-				]);
+				let (load_cell_info, mut instrs) =
+					LoadCellInformation::create(offset, 0, None, spec);
+
+				instrs.extend([BrainInstructionType::PerformBinaryValueOperation {
+					lhs: RegOrImm::Reg(load_cell_info.cell_reg),
+					rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
+					output_reg: Register::new(load_cell_info.instr_offset),
+					op: BinaryOperation::Add,
+				}]);
+
+				instrs.extend(check_cell_overflow(
+					load_cell_info.cell_reg,
+					Register::new(load_cell_info.instr_offset),
+					BinaryOperation::Add,
+					spec,
+				));
+
+				instrs.push(BrainInstructionType::StoreValueIntoCell {
+					value: RegOrImm::Reg(Register::new(load_cell_info.instr_offset)),
+					pointer_reg: load_cell_info.pointer_reg,
+				});
 
 				instrs
 					.into_iter()
@@ -207,20 +238,27 @@ impl ToInstructions for BrainOperation {
 					.collect()
 			}
 			&BrainOperationType::DecrementCell(CellOffsetOptions { value, offset }) => {
-				let (load_cell_info, mut instrs) = LoadCellInformation::create(offset, 0, None);
-
-				instrs.extend([
-					BrainInstructionType::PerformBinaryValueOperation {
-						lhs: RegOrImm::Reg(load_cell_info.cell_reg),
-						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
-						output_reg: Register::new(load_cell_info.instr_offset),
-						op: BinaryOperation::Sub,
-					},
-					BrainInstructionType::StoreValueIntoCell {
-						value: RegOrImm::Reg(Register::new(load_cell_info.instr_offset)),
-						pointer_reg: load_cell_info.pointer_reg,
-					},
-				]);
+				let (load_cell_info, mut instrs) =
+					LoadCellInformation::create(offset, 0, None, spec);
+
+				instrs.extend([BrainInstructionType::PerformBinaryValueOperation {
+					lhs: RegOrImm::Reg(load_cell_info.cell_reg),
+					rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
+					output_reg: Register::new(load_cell_info.instr_offset),
+					op: BinaryOperation::Sub,
+				}]);
+
+				instrs.extend(check_cell_overflow(
+					load_cell_info.cell_reg,
+					Register::new(load_cell_info.instr_offset),
+					BinaryOperation::Sub,
+					spec,
+				));
+
+				instrs.push(BrainInstructionType::StoreValueIntoCell {
+					value: RegOrImm::Reg(Register::new(load_cell_info.instr_offset)),
+					pointer_reg: load_cell_info.pointer_reg,
+				});
 
 				instrs
 					.into_iter()
@@ -236,87 +274,95 @@ impl ToInstructions for BrainOperation {
 					output_reg: Register::new(1),
 				},
 				BrainInstructionType::StoreValueIntoCell {
-					value: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
+					value: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
 					pointer_reg: Register::new(1),
 				},
 			]
 			.into_iter()
 			.map(|x| BrainInstruction::new(x, self.span().start))
 			.collect(),
-			&BrainOperationType::SetCell(CellOffsetOptions { value, offset }) => [
-				BrainInstructionType::LoadTapePointerIntoRegister {
-					output_reg: Register::new(0),
-				},
-				BrainInstructionType::StoreImmediateIntoRegister {
-					imm: Immediate::pointer(offset.unsigned_abs().convert::<u64>()),
-					output_reg: Register::new(1),
-				},
-				BrainInstructionType::PerformBinaryValueOperation {
-					lhs: RegOrImm::Reg(Register::new(0)),
-					rhs: RegOrImm::Imm(Immediate::pointer(offset.unsigned_abs().convert::<u64>())),
-					output_reg: Register::new(1),
-					op: if offset.is_positive() {
-						BinaryOperation::Add
-					} else {
-						BinaryOperation::Sub
+			&BrainOperationType::SetCell(CellOffsetOptions { value, offset }) => {
+				let mut instrs = vec![
+					BrainInstructionType::LoadTapePointerIntoRegister {
+						output_reg: Register::new(0),
 					},
-				},
-				BrainInstructionType::PerformBinaryValueOperation {
-					lhs: RegOrImm::Reg(Register::new(1)),
-					rhs: RegOrImm::Imm(Immediate::TAPE_SIZE_MINUS_ONE),
-					output_reg: Register::new(2),
-					op: BinaryOperation::BitwiseAnd,
-				},
-				BrainInstructionType::CalculateTapeOffset {
-					tape_pointer_reg: Register::new(2),
-					output_reg: Register::new(3),
-				},
-				BrainInstructionType::StoreValueIntoCell {
-					value: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
-					pointer_reg: Register::new(3),
-				},
-			]
-			.into_iter()
-			.map(|x| BrainInstruction::new(x, self.span().start))
-			.collect(),
-			&BrainOperationType::MovePointer(offset) => [
-				BrainInstructionType::LoadTapePointerIntoRegister {
-					output_reg: Register::new(0),
-				},
-				BrainInstructionType::PerformBinaryValueOperation {
-					lhs: RegOrImm::Reg(Register::new(0)),
-					rhs: RegOrImm::Imm(Immediate::pointer(offset.unsigned_abs().convert::<u64>())),
-					output_reg: Register::new(1),
-					op: if offset.is_positive() {
-						BinaryOperation::Add
-					} else {
-						BinaryOperation::Sub
+					BrainInstructionType::StoreImmediateIntoRegister {
+						imm: Immediate::pointer(offset.unsigned_abs().convert::<u64>()),
+						output_reg: Register::new(1),
 					},
-				},
-				BrainInstructionType::PerformBinaryValueOperation {
-					lhs: RegOrImm::Reg(Register::new(1)),
-					rhs: RegOrImm::Imm(Immediate::TAPE_SIZE_MINUS_ONE),
-					output_reg: Register::new(2),
-					op: BinaryOperation::BitwiseAnd,
-				},
-				BrainInstructionType::StoreRegisterIntoTapePointer {
+					BrainInstructionType::PerformBinaryValueOperation {
+						lhs: RegOrImm::Reg(Register::new(0)),
+						rhs: RegOrImm::Imm(Immediate::pointer(
+							offset.unsigned_abs().convert::<u64>(),
+						)),
+						output_reg: Register::new(1),
+						op: if offset.is_positive() {
+							BinaryOperation::Add
+						} else {
+							BinaryOperation::Sub
+						},
+					},
+				];
+
+				instrs.extend(bound_tape_pointer(Register::new(1), Register::new(2), spec));
+
+				instrs.extend([
+					BrainInstructionType::CalculateTapeOffset {
+						tape_pointer_reg: Register::new(2),
+						output_reg: Register::new(3),
+					},
+					BrainInstructionType::StoreValueIntoCell {
+						value: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
+						pointer_reg: Register::new(3),
+					},
+				]);
+
+				instrs
+					.into_iter()
+					.map(|x| BrainInstruction::new(x, self.span().start))
+					.collect()
+			}
+			&BrainOperationType::MovePointer(offset) => {
+				let mut instrs = vec![
+					BrainInstructionType::LoadTapePointerIntoRegister {
+						output_reg: Register::new(0),
+					},
+					BrainInstructionType::PerformBinaryValueOperation {
+						lhs: RegOrImm::Reg(Register::new(0)),
+						rhs: RegOrImm::Imm(Immediate::pointer(
+							offset.unsigned_abs().convert::<u64>(),
+						)),
+						output_reg: Register::new(1),
+						op: if offset.is_positive() {
+							BinaryOperation::Add
+						} else {
+							BinaryOperation::Sub
+						},
+					},
+				];
+
+				instrs.extend(bound_tape_pointer(Register::new(1), Register::new(2), spec));
+
+				instrs.push(BrainInstructionType::StoreRegisterIntoTapePointer {
 					input_reg: Register::new(2),
-				},
-			]
-			.into_iter()
-			.map(|x| BrainInstruction::new(x, self.span().start))
-			.collect(),
+				});
+
+				instrs
+					.into_iter()
+					.map(|x| BrainInstruction::new(x, self.span().start))
+					.collect()
+			}
 			&BrainOperationType::MoveCellValue(CellOffsetOptions { value, offset }) => {
-				let (current_cell_info, mut instrs) = LoadCellInformation::create(0, 0, None);
+				let (current_cell_info, mut instrs) = LoadCellInformation::create(0, 0, None, spec);
 
 				instrs.extend([
 					BrainInstructionType::StoreValueIntoCell {
-						value: RegOrImm::Imm(Immediate::CELL_ZERO),
+						value: RegOrImm::Imm(Immediate::cell_zero(spec)),
 						pointer_reg: current_cell_info.pointer_reg,
 					},
 					BrainInstructionType::PerformBinaryValueOperation {
 						lhs: RegOrImm::Reg(current_cell_info.cell_reg),
-						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
+						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
 						output_reg: Register::new(current_cell_info.instr_offset + 1),
 						op: BinaryOperation::Mul,
 					},
@@ -326,6 +372,7 @@ impl ToInstructions for BrainOperation {
 					offset,
 					current_cell_info.instr_offset + 2,
 					Some(current_cell_info.tape_pointer_reg),
+					spec,
 				);
 
 				instrs.append(&mut other_cell_instrs);
@@ -349,16 +396,16 @@ impl ToInstructions for BrainOperation {
 					.collect()
 			}
 			&BrainOperationType::TakeCellValue(CellOffsetOptions { value, offset }) => {
-				let (current_cell_info, mut instrs) = LoadCellInformation::create(0, 0, None);
+				let (current_cell_info, mut instrs) = LoadCellInformation::create(0, 0, None, spec);
 
 				instrs.extend([
 					BrainInstructionType::StoreValueIntoCell {
-						value: RegOrImm::Imm(Immediate::CELL_ZERO),
+						value: RegOrImm::Imm(Immediate::cell_zero(spec)),
 						pointer_reg: current_cell_info.pointer_reg,
 					},
 					BrainInstructionType::PerformBinaryValueOperation {
 						lhs: RegOrImm::Reg(current_cell_info.cell_reg),
-						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
+						rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
 						output_reg: Register::new(current_cell_info.instr_offset + 1),
 						op: BinaryOperation::Mul,
 					},
@@ -368,6 +415,7 @@ impl ToInstructions for BrainOperation {
 					offset,
 					current_cell_info.instr_offset + 2,
 					Some(current_cell_info.tape_pointer_reg),
+					spec,
 				);
 
 				instrs.append(&mut other_cell_instrs);
@@ -393,6 +441,21 @@ impl ToInstructions for BrainOperation {
 					.map(|i| BrainInstruction::new(i, self.span().start))
 					.collect()
 			}
+			&BrainOperationType::InputIntoCell if matches!(spec.eof(), EofMode::Unchanged) => [
+				BrainInstructionType::LoadTapePointerIntoRegister {
+					output_reg: Register::new(0),
+				},
+				BrainInstructionType::CalculateTapeOffset {
+					tape_pointer_reg: Register::new(0),
+					output_reg: Register::new(1),
+				},
+				BrainInstructionType::InputIntoCell {
+					pointer_reg: Register::new(1),
+				},
+			]
+			.into_iter()
+			.map(|x| BrainInstruction::new(x, self.span().start))
+			.collect(),
 			&BrainOperationType::InputIntoCell => [
 				BrainInstructionType::InputIntoRegister {
 					output_reg: Register::new(0),
@@ -413,7 +476,8 @@ impl ToInstructions for BrainOperation {
 			.map(|x| BrainInstruction::new(x, self.span().start))
 			.collect(),
 			&BrainOperationType::OutputCell(CellOffsetOptions { value, offset }) => {
-				let (load_cell_info, mut instrs) = LoadCellInformation::create(offset, 0, None);
+				let (load_cell_info, mut instrs) =
+					LoadCellInformation::create(offset, 0, None, spec);
 
 				if matches!(value, 0) {
 					instrs.push(BrainInstructionType::OutputFromRegister {
@@ -423,7 +487,7 @@ impl ToInstructions for BrainOperation {
 					instrs.extend([
 						BrainInstructionType::PerformBinaryValueOperation {
 							lhs: RegOrImm::Reg(load_cell_info.cell_reg),
-							rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>())),
+							rhs: RegOrImm::Imm(Immediate::cell(value.convert::<u64>(), spec)),
 							output_reg: Register::new(load_cell_info.instr_offset),
 							op: BinaryOperation::Add,
 						},
@@ -440,7 +504,7 @@ impl ToInstructions for BrainOperation {
 			}
 			&BrainOperationType::OutputValue(value) => [
 				BrainInstructionType::StoreImmediateIntoRegister {
-					imm: Immediate::cell(value.convert::<u64>()),
+					imm: Immediate::cell(value.convert::<u64>(), spec),
 					output_reg: Register::new(0),
 				},
 				BrainInstructionType::OutputFromRegister {
@@ -466,7 +530,7 @@ impl ToInstructions for BrainOperation {
 					},
 					BrainInstructionType::CompareValues {
 						lhs: RegOrImm::Reg(Register::new(2)),
-						rhs: RegOrImm::Imm(Immediate::CELL_ZERO),
+						rhs: RegOrImm::Imm(Immediate::cell_zero(spec)),
 						output_reg: Register::new(3),
 					},
 					BrainInstructionType::JumpIf {
@@ -478,7 +542,7 @@ impl ToInstructions for BrainOperation {
 				.collect::<Vec<_>>();
 
 				for op in ops {
-					output.extend(op.to_instructions());
+					output.extend(op.to_instructions(spec));
 				}
 
 				output.extend(
@@ -1,40 +1,510 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "test_util")]
+pub mod test_util;
+
 use std::{
-	ffi::c_void,
+	cell::{Cell, RefCell},
+	ffi::{CStr, c_char, c_void},
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
 	io::{self, prelude::*},
-	process::abort,
-	slice,
+	mem,
+	process::{self, abort},
+	ptr, slice,
+	time::{Duration, Instant},
 };
 
+use frick_spec::OUTPUT_BUFFER_SIZE;
+// Kept per thread like `HOST_IO`, so a flush only ever hands bytes to the writer of the program
+// that output them
+thread_local! {
+	static OUTPUT: RefCell<Output> = const {
+		RefCell::new(Output {
+			buffer: Vec::new(),
+			threshold: OUTPUT_BUFFER_SIZE,
+		})
+	};
+	static HOST_IO: Cell<Option<HostIo>> = const { Cell::new(None) };
+	static HOST_IO_ERROR: Cell<Option<io::Error>> = const { Cell::new(None) };
+	static SNAPSHOT: RefCell<SnapshotState> = const { RefCell::new(SnapshotState::Off) };
+	static METER: Cell<Meter> = const { Cell::new(Meter::UNLIMITED) };
+	static TRAP: RefCell<Option<Trap>> = const { RefCell::new(None) };
+}
+
+struct Output {
+	buffer: Vec<u8>,
+	threshold: usize,
+}
+
+impl Output {
+	fn flush(&mut self) {
+		if let Some(host_io) = HOST_IO.get() {
+			// once the host writer has failed, the rest of the output is dropped
+			if !has_host_io_error() {
+				let written = unsafe {
+					(*host_io.writer)
+						.write_all(&self.buffer)
+						.and_then(|()| (*host_io.writer).flush())
+				};
+
+				if let Err(e) = written {
+					set_host_io_error(e);
+				}
+			}
+		} else {
+			let mut stdout = io::stdout().lock();
+
+			if stdout
+				.write_all(&self.buffer)
+				.and_then(|()| stdout.flush())
+				.is_err()
+			{
+				abort();
+			}
+		}
+
+		self.buffer.clear();
+	}
+}
+
+#[derive(Clone, Copy)]
+struct HostIo {
+	reader: *mut (dyn Read + 'static),
+	writer: *mut (dyn Write + 'static),
+}
+
+/// Runs `f` with program input read from `reader` and output written to `writer` instead of
+/// stdin and stdout, for the current thread only.
+///
+/// Returns the first error either of them gave, a failed read is seen by the program as the end
+/// of input and a failed write drops the rest of its output.
+pub fn with_io<R>(
+	reader: &mut dyn Read,
+	writer: &mut dyn Write,
+	f: impl FnOnce() -> R,
+) -> io::Result<R> {
+	struct Reset {
+		host_io: Option<HostIo>,
+		error: Option<io::Error>,
+	}
+
+	impl Drop for Reset {
+		fn drop(&mut self) {
+			flush_output();
+			HOST_IO.set(self.host_io);
+			HOST_IO_ERROR.set(self.error.take());
+		}
+	}
+
+	// anything still buffered belongs to whoever was writing before
+	flush_output();
+
+	// SAFETY: the pointers are only stored in this thread's `HOST_IO`, which `Reset` puts back to
+	// its previous value before this function returns or unwinds, so they are never used once the
+	// borrows of `reader` and `writer` end
+	let host_io = unsafe {
+		HostIo {
+			reader: ptr::from_mut(mem::transmute::<&mut dyn Read, &mut (dyn Read + 'static)>(
+				reader,
+			)),
+			writer: ptr::from_mut(
+				mem::transmute::<&mut dyn Write, &mut (dyn Write + 'static)>(writer),
+			),
+		}
+	};
+
+	let _reset = Reset {
+		host_io: HOST_IO.replace(Some(host_io)),
+		error: HOST_IO_ERROR.take(),
+	};
+
+	let result = f();
+
+	flush_output();
+
+	match HOST_IO_ERROR.take() {
+		Some(e) => Err(e),
+		None => Ok(result),
+	}
+}
+
+/// The state of the tape when a program finished running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapeSnapshot {
+	tape: Vec<u32>,
+	pointer: usize,
+}
+
+impl TapeSnapshot {
+	#[must_use]
+	pub const fn new(tape: Vec<u32>, pointer: usize) -> Self {
+		Self { tape, pointer }
+	}
+
+	#[must_use]
+	pub fn tape(&self) -> &[u32] {
+		&self.tape
+	}
+
+	#[must_use]
+	pub const fn pointer(&self) -> usize {
+		self.pointer
+	}
+
+	#[must_use]
+	pub fn cell(&self, index: usize) -> Option<u32> {
+		self.tape.get(index).copied()
+	}
+
+	#[must_use]
+	pub fn current_cell(&self) -> u32 {
+		self.tape[self.pointer]
+	}
+
+	#[must_use]
+	pub fn into_tape(self) -> Vec<u32> {
+		self.tape
+	}
+}
+
+enum SnapshotState {
+	Off,
+	Requested,
+	Taken(TapeSnapshot),
+}
+
+/// Runs `f`, capturing the tape of any program that finishes on the current thread meanwhile.
+pub fn with_snapshot<R>(f: impl FnOnce() -> R) -> (R, Option<TapeSnapshot>) {
+	let previous = SNAPSHOT.replace(SnapshotState::Requested);
+
+	let result = f();
+
+	let snapshot = match SNAPSHOT.replace(previous) {
+		SnapshotState::Taken(snapshot) => Some(snapshot),
+		SnapshotState::Off | SnapshotState::Requested => None,
+	};
+
+	(result, snapshot)
+}
+
+/// Called when a program exits, only copies the tape if a snapshot was requested.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rust_putchar(c: u8) {
-	let mut stdout = io::stdout().lock();
+pub unsafe extern "C" fn rust_snapshot_tape(
+	tape: *const c_void,
+	len: usize,
+	pointer: usize,
+	cell_bytes: usize,
+) {
+	SNAPSHOT.with_borrow_mut(|state| {
+		if matches!(state, SnapshotState::Off) {
+			return;
+		}
+
+		*state = SnapshotState::Taken(TapeSnapshot {
+			tape: (0..len)
+				.map(|index| unsafe { load_cell(tape, index, cell_bytes) })
+				.collect(),
+			pointer,
+		});
+	});
+}
 
-	if stdout
-		.write_all(slice::from_ref(&c))
-		.and_then(|()| stdout.flush())
-		.is_err()
-	{
-		abort();
+/// Where in the source a program trapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+	file_name: String,
+	line: u32,
+	column: u32,
+}
+
+impl SourceLocation {
+	#[must_use]
+	pub fn file_name(&self) -> &str {
+		&self.file_name
 	}
+
+	#[must_use]
+	pub const fn line(&self) -> u32 {
+		self.line
+	}
+
+	#[must_use]
+	pub const fn column(&self) -> u32 {
+		self.column
+	}
+
+	unsafe fn new(file_name: *const c_char, line: u32, column: u32) -> Self {
+		let file_name = if file_name.is_null() {
+			"<unknown>".to_owned()
+		} else {
+			unsafe { CStr::from_ptr(file_name) }
+				.to_string_lossy()
+				.into_owned()
+		};
+
+		Self {
+			file_name,
+			line,
+			column,
+		}
+	}
+}
+
+impl Display for SourceLocation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(&self.file_name)?;
+		f.write_str(":")?;
+		Display::fmt(&self.line, f)?;
+		f.write_str(":")?;
+		Display::fmt(&self.column, f)
+	}
+}
+
+/// Why a program stopped itself partway through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trap {
+	OutOfBounds {
+		pointer: isize,
+		location: SourceLocation,
+	},
+	CellOverflow {
+		underflow: bool,
+		location: SourceLocation,
+	},
+}
+
+impl Trap {
+	#[must_use]
+	pub const fn location(&self) -> &SourceLocation {
+		match self {
+			Self::OutOfBounds { location, .. } | Self::CellOverflow { location, .. } => location,
+		}
+	}
+
+	// the runtime is only hosted while `with_io` has its reader and writer installed, standalone
+	// programs own the process and report the trap on the way out
+	fn raise(self) {
+		flush_output();
+
+		if HOST_IO.get().is_some() {
+			TRAP.set(Some(self));
+			return;
+		}
+
+		match &self {
+			Self::OutOfBounds { pointer, .. } => {
+				eprintln!("error: tape pointer moved out of bounds to {pointer}");
+			}
+			Self::CellOverflow {
+				underflow: true, ..
+			} => {
+				eprintln!("error: cell decremented below zero");
+			}
+			Self::CellOverflow {
+				underflow: false, ..
+			} => eprintln!("error: cell incremented past its maximum value"),
+		}
+
+		eprintln!("  --> {}", self.location());
+
+		process::exit(1)
+	}
+}
+
+impl Display for Trap {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::OutOfBounds { pointer, .. } => {
+				f.write_str("tape pointer moved out of bounds to ")?;
+				Display::fmt(pointer, f)?;
+			}
+			Self::CellOverflow {
+				underflow: true, ..
+			} => {
+				f.write_str("cell decremented below zero")?;
+			}
+			Self::CellOverflow {
+				underflow: false, ..
+			} => f.write_str("cell incremented past its maximum value")?,
+		}
+
+		f.write_str(" at ")?;
+		Display::fmt(self.location(), f)
+	}
+}
+
+/// Runs `f`, returning the trap any program on the current thread hit meanwhile.
+///
+/// Traps are only caught while [`with_io`] is in effect, the program then returns early instead
+/// of the runtime exiting the process.
+pub fn with_trap<R>(f: impl FnOnce() -> R) -> (R, Option<Trap>) {
+	let previous = TRAP.take();
+
+	let result = f();
+
+	(result, TRAP.replace(previous))
+}
+
+/// How many loop iterations a metered program may run between checks of its limits.
+const FUEL_CHUNK: u64 = 0x10000;
+
+/// Why a metered program was stopped before it finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+	StepLimit(u64),
+	Timeout(Duration),
+}
+
+impl Display for StopReason {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::StepLimit(steps) => {
+				f.write_str("step limit of ")?;
+				Display::fmt(steps, f)?;
+				f.write_str(" exceeded")
+			}
+			Self::Timeout(timeout) => {
+				f.write_str("timed out after ")?;
+				Debug::fmt(timeout, f)
+			}
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+struct Meter {
+	step_limit: u64,
+	steps_left: u64,
+	started: Option<Instant>,
+	timeout: Option<Duration>,
+	stopped: Option<StopReason>,
+	hosted: bool,
 }
 
+impl Meter {
+	const UNLIMITED: Self = Self {
+		step_limit: u64::MAX,
+		steps_left: u64::MAX,
+		started: None,
+		timeout: None,
+		stopped: None,
+		hosted: false,
+	};
+}
+
+/// Runs `f`, returning why any metered program on the current thread was stopped meanwhile
+/// instead of reporting it on stderr.
+pub fn with_meter<R>(f: impl FnOnce() -> R) -> (R, Option<StopReason>) {
+	let previous = METER.replace(Meter {
+		hosted: true,
+		..Meter::UNLIMITED
+	});
+
+	let result = f();
+
+	(result, METER.replace(previous).stopped)
+}
+
+/// Called at the start of a metered program, `u64::MAX` means no limit for either argument.
 #[unsafe(no_mangle)]
-#[must_use]
-pub unsafe extern "C" fn rust_getchar() -> u8 {
-	let mut stdin = io::stdin().lock();
+pub extern "C" fn rust_start_meter(step_limit: u64, timeout_millis: u64) {
+	let hosted = METER.get().hosted;
 
-	let mut value = 0;
+	METER.set(Meter {
+		step_limit,
+		steps_left: step_limit,
+		started: Some(Instant::now()),
+		timeout: (timeout_millis != u64::MAX).then(|| Duration::from_millis(timeout_millis)),
+		stopped: None,
+		hosted,
+	});
+}
+
+/// Called whenever a metered program runs out of loop iterations, returns how many more it may
+/// run or 0 if it has to stop.
+#[unsafe(no_mangle)]
+pub extern "C" fn rust_refuel() -> u64 {
+	let mut meter = METER.get();
 
-	if let Err(e) = stdin.read_exact(slice::from_mut(&mut value)) {
-		match e.kind() {
-			io::ErrorKind::UnexpectedEof => value = 10,
-			_ => abort(),
+	let timed_out = meter
+		.started
+		.zip(meter.timeout)
+		.is_some_and(|(started, timeout)| started.elapsed() >= timeout);
+
+	let fuel = meter.steps_left.min(FUEL_CHUNK);
+
+	if timed_out {
+		meter.stopped = meter.timeout.map(StopReason::Timeout);
+	} else if fuel == 0 {
+		meter.stopped = Some(StopReason::StepLimit(meter.step_limit));
+	}
+
+	let fuel = if meter.stopped.is_some() { 0 } else { fuel };
+
+	meter.steps_left -= fuel;
+	METER.set(meter);
+
+	fuel
+}
+
+/// Called as a metered program exits, returning its exit code.
+#[unsafe(no_mangle)]
+pub extern "C" fn rust_finish_meter() -> i32 {
+	let meter = METER.get();
+
+	match meter.stopped {
+		Some(reason) => {
+			if !meter.hosted {
+				eprintln!("error: {reason}");
+			}
+
+			1
 		}
+		None => 0,
 	}
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_putchar(c: u8) {
+	OUTPUT.with_borrow_mut(|output| {
+		output.buffer.push(c);
+
+		if output.buffer.len() >= output.threshold {
+			output.flush();
+		}
+	});
+}
+
+/// Sets how many bytes of output are buffered before being written, 0 writes every byte as
+/// soon as it is output.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_set_output_threshold(threshold: usize) {
+	OUTPUT.with_borrow_mut(|output| {
+		output.threshold = threshold;
+
+		if output.buffer.len() >= threshold {
+			output.flush();
+		}
+	});
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_flush() {
+	flush_output();
+}
 
-	value
+#[unsafe(no_mangle)]
+#[must_use]
+pub unsafe extern "C" fn rust_getchar(eof_value: u32) -> u32 {
+	read_byte().map_or(eof_value, u32::from)
+}
+
+/// Reads a byte into `cell`, leaving it untouched once input has run out.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_getchar_into(cell: *mut c_void, cell_bytes: usize) {
+	if let Some(value) = read_byte() {
+		unsafe { store_byte(cell, value, cell_bytes) };
+	}
 }
 
 #[unsafe(no_mangle)]
@@ -47,3 +517,251 @@ pub unsafe extern "C" fn rust_alloc(size: usize) -> *mut c_void {
 pub unsafe extern "C" fn rust_free(ptr: *mut c_void) {
 	unsafe { libc::free(ptr) }
 }
+
+/// Reallocates the tape so `index` is in bounds, zeroing the new cells.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_grow_tape(
+	tape: *mut *mut u8,
+	len: *mut usize,
+	index: usize,
+	cell_bytes: usize,
+) {
+	let Some(new_len) = index
+		.checked_add(1)
+		.and_then(usize::checked_next_power_of_two)
+	else {
+		fail();
+	};
+
+	unsafe {
+		let old_tape = *tape;
+		let old_len = *len;
+
+		let Some(new_size) = new_len.checked_mul(cell_bytes) else {
+			fail();
+		};
+
+		let new_tape = rust_alloc(new_size).cast::<u8>();
+
+		if new_tape.is_null() {
+			fail();
+		}
+
+		let old_size = old_len * cell_bytes;
+
+		ptr::copy_nonoverlapping(old_tape, new_tape, old_size);
+		ptr::write_bytes(new_tape.add(old_size), 0, new_size - old_size);
+
+		rust_free(old_tape.cast());
+
+		*tape = new_tape;
+		*len = new_len;
+	}
+}
+
+/// Exits the process, unless the program runs under [`with_io`] and can return early instead.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_tape_out_of_bounds(
+	file_name: *const c_char,
+	line: u32,
+	column: u32,
+	pointer: isize,
+) {
+	Trap::OutOfBounds {
+		pointer,
+		location: unsafe { SourceLocation::new(file_name, line, column) },
+	}
+	.raise();
+}
+
+/// Exits the process, unless the program runs under [`with_io`] and can return early instead.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rust_cell_overflow(
+	file_name: *const c_char,
+	line: u32,
+	column: u32,
+	underflow: bool,
+) {
+	Trap::CellOverflow {
+		underflow,
+		location: unsafe { SourceLocation::new(file_name, line, column) },
+	}
+	.raise();
+}
+
+// Programs are usually prompting for whatever comes next, so it has to be visible before blocking
+fn read_byte() -> Option<u8> {
+	flush_output();
+
+	let mut value = 0;
+
+	let read = match HOST_IO.get() {
+		Some(host_io) => unsafe { (*host_io.reader).read_exact(slice::from_mut(&mut value)) },
+		None => io::stdin().lock().read_exact(slice::from_mut(&mut value)),
+	};
+
+	match read {
+		Ok(()) => Some(value),
+		Err(e) if matches!(e.kind(), io::ErrorKind::UnexpectedEof) => None,
+		Err(e) if HOST_IO.get().is_some() => {
+			set_host_io_error(e);
+			None
+		}
+		Err(..) => fail(),
+	}
+}
+
+unsafe fn load_cell(tape: *const c_void, index: usize, cell_bytes: usize) -> u32 {
+	unsafe {
+		match cell_bytes {
+			1 => tape.cast::<u8>().add(index).read().into(),
+			2 => tape.cast::<u16>().add(index).read_unaligned().into(),
+			_ => tape.cast::<u32>().add(index).read_unaligned(),
+		}
+	}
+}
+
+unsafe fn store_byte(cell: *mut c_void, value: u8, cell_bytes: usize) {
+	unsafe {
+		match cell_bytes {
+			1 => cell.cast::<u8>().write(value),
+			2 => cell.cast::<u16>().write_unaligned(value.into()),
+			_ => cell.cast::<u32>().write_unaligned(value.into()),
+		}
+	}
+}
+
+fn flush_output() {
+	OUTPUT.with_borrow_mut(Output::flush);
+}
+
+fn has_host_io_error() -> bool {
+	let error = HOST_IO_ERROR.take();
+	let has_error = error.is_some();
+
+	HOST_IO_ERROR.set(error);
+
+	has_error
+}
+
+// Only the first error is kept, anything after it is usually caused by it
+fn set_host_io_error(e: io::Error) {
+	let error = HOST_IO_ERROR.take().unwrap_or(e);
+
+	HOST_IO_ERROR.set(Some(error));
+}
+
+// Keeps whatever the program managed to output before bailing out
+fn fail() -> ! {
+	_ = OUTPUT.try_with(|output| {
+		if let Ok(mut output) = output.try_borrow_mut() {
+			output.flush();
+		}
+	});
+
+	abort()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io::{self, prelude::*},
+		thread,
+	};
+
+	use super::{
+		Trap, rust_cell_overflow, rust_getchar, rust_putchar, rust_set_output_threshold, with_io,
+		with_trap,
+	};
+
+	struct FailingWriter;
+
+	impl Write for FailingWriter {
+		fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+			Err(io::Error::other("writer failed"))
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	fn echo() {
+		unsafe {
+			loop {
+				let c = rust_getchar(u32::MAX);
+
+				if c == u32::MAX {
+					break;
+				}
+
+				rust_putchar(c as u8);
+			}
+		}
+	}
+
+	#[test]
+	fn runs_into_vec_writer() {
+		let mut output = Vec::new();
+
+		with_io(&mut &b"hello"[..], &mut output, echo).unwrap();
+
+		assert_eq!(output, b"hello");
+	}
+
+	#[test]
+	fn threads_keep_their_own_output() {
+		let threads = b"abcd".map(|c| {
+			thread::spawn(move || {
+				let input = vec![c; 0x10000];
+				let mut output = Vec::new();
+
+				with_io(&mut input.as_slice(), &mut output, echo).unwrap();
+
+				(input, output)
+			})
+		});
+
+		for thread in threads {
+			let (input, output) = thread.join().unwrap();
+
+			assert_eq!(input, output);
+		}
+	}
+
+	#[test]
+	fn failing_writer_is_returned() {
+		let error = with_io(&mut &b"hello"[..], &mut FailingWriter, || unsafe {
+			rust_set_output_threshold(0);
+			echo();
+		})
+		.unwrap_err();
+
+		assert_eq!(error.to_string(), "writer failed");
+	}
+
+	#[test]
+	fn hosted_trap_is_returned() {
+		let mut output = Vec::new();
+
+		let ((), trap) = with_trap(|| {
+			with_io(&mut io::empty(), &mut output, || unsafe {
+				rust_putchar(b'x');
+				rust_cell_overflow(c"test.bf".as_ptr(), 1, 2, true);
+			})
+			.unwrap();
+		});
+
+		let Some(Trap::CellOverflow {
+			underflow: true,
+			location,
+		}) = trap
+		else {
+			panic!("expected an underflow, got {trap:?}");
+		};
+
+		assert_eq!(location.to_string(), "test.bf:1:2");
+		assert_eq!(output, b"x");
+		assert_eq!(with_trap(|| ()).1, None);
+	}
+}
@@ -0,0 +1,128 @@
+//! Checks shared by the JIT backends, each compiling programs against this runtime.
+
+use std::{env, fmt::Debug, process::Command};
+
+use frick_spec::{CellOverflow, Spec, TapeBoundary};
+
+use crate::{TapeSnapshot, Trap};
+
+// without host I/O the runtime exits the process when a program traps, so those programs run again
+// in a child test process, named by this variable
+const STANDALONE_PROGRAM: &str = "FRICK_STANDALONE_PROGRAM";
+
+/// A backend compiling programs from a source file named `test.bf`.
+pub trait Backend {
+	type Error: Debug;
+
+	/// Runs the program with the process stdin and stdout.
+	fn execute(program: &str, spec: Spec) -> Result<(), Self::Error>;
+
+	/// Runs the program with no input, writing its output to `output`.
+	fn execute_with(program: &str, spec: Spec, output: &mut Vec<u8>) -> Result<(), Self::Error>;
+
+	/// Like [`Self::execute_with`], returning the final tape.
+	fn execute_snapshot_with(
+		program: &str,
+		spec: Spec,
+		output: &mut Vec<u8>,
+	) -> Result<TapeSnapshot, Self::Error>;
+
+	/// The trap a hosted program stopped with, if that's what `error` is.
+	fn trap(error: Self::Error) -> Option<Trap>;
+}
+
+/// Runs `program` standalone in a child running `test` again, returning its stderr after checking
+/// that it exited with an error.
+///
+/// Returns [`None`] in the child, once `program` has run.
+#[must_use]
+pub fn run_standalone<B: Backend>(test: &str, program: &str, spec: Spec) -> Option<String> {
+	if let Some(child) = env::var_os(STANDALONE_PROGRAM) {
+		if child == program {
+			_ = B::execute(program, spec);
+		}
+
+		return None;
+	}
+
+	let output = Command::new(env::current_exe().unwrap())
+		.args(["--exact", test, "--nocapture"])
+		.env(STANDALONE_PROGRAM, program)
+		.output()
+		.unwrap();
+
+	assert_eq!(output.status.code(), Some(1), "{program}");
+
+	Some(String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+/// Checks that the final tape includes work done after the last output.
+pub fn check_snapshots<B: Backend>() {
+	let spec = Spec::new().with_strict_semantics(true);
+
+	let snapshot = B::execute_snapshot_with("+++>++", spec, &mut Vec::new()).unwrap();
+
+	assert_eq!(snapshot.pointer(), 1);
+	assert_eq!(snapshot.cell(0), Some(3));
+	assert_eq!(snapshot.cell(1), Some(2));
+
+	let snapshot = B::execute_snapshot_with("++++++[->+++++++<]>", spec, &mut Vec::new()).unwrap();
+
+	assert_eq!(snapshot.pointer(), 1);
+	assert_eq!(snapshot.current_cell(), 42);
+	assert_eq!(snapshot.cell(0), Some(0));
+}
+
+/// Checks the messages of standalone programs that overflow a cell, `test` being the path of the
+/// calling test.
+pub fn check_standalone_overflow<B: Backend>(test: &str) {
+	let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+	for (program, message, location) in [
+		(
+			&*"+".repeat(256),
+			"cell incremented past its maximum value",
+			"test.bf:1:256",
+		),
+		("+>-", "cell decremented below zero", "test.bf:1:3"),
+	] {
+		let Some(stderr) = run_standalone::<B>(test, program, spec) else {
+			continue;
+		};
+
+		assert!(stderr.contains(message), "{stderr}");
+		assert!(stderr.contains(location), "{stderr}");
+	}
+}
+
+/// Checks that hosted programs return their traps, along with the output before them.
+pub fn check_hosted_traps<B: Backend>() {
+	let mut output = Vec::new();
+	let error = B::execute_with(
+		"+.>-",
+		Spec::new().with_overflow(CellOverflow::Trap),
+		&mut output,
+	)
+	.unwrap_err();
+
+	let Some(Trap::CellOverflow {
+		underflow: true,
+		location,
+	}) = B::trap(error)
+	else {
+		panic!("expected an underflow");
+	};
+
+	assert_eq!(output, [1]);
+	assert_eq!(location.file_name(), "test.bf");
+	assert_eq!((location.line(), location.column()), (1, 4));
+
+	let spec = Spec::new()
+		.with_boundary(TapeBoundary::Abort)
+		.with_strict_semantics(true);
+
+	assert!(matches!(
+		B::execute_with("<", spec, &mut Vec::new()).map_err(B::trap),
+		Err(Some(Trap::OutOfBounds { pointer: -1, .. }))
+	));
+}
@@ -0,0 +1,103 @@
+use std::ops::Range;
+
+use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+
+use super::InterpreterError;
+
+/// A flattened [`BrainOperation`], with loops turned into jumps to absolute indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bytecode {
+	Add { value: u32, offset: i32 },
+	Sub { value: u32, offset: i32 },
+	Set { value: u32, offset: i32 },
+	Move(i32),
+	MoveValue { factor: u32, offset: i32 },
+	TakeValue { factor: u32, offset: i32 },
+	Input,
+	Output { value: u32, offset: i32 },
+	OutputValue(u8),
+	JumpIfZero(usize),
+	JumpUnlessZero(usize),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Program {
+	code: Vec<Bytecode>,
+	spans: Vec<Range<usize>>,
+}
+
+impl Program {
+	pub fn compile(ops: &[BrainOperation]) -> Result<Self, InterpreterError> {
+		let mut program = Self::default();
+
+		program.compile_into(ops)?;
+
+		Ok(program)
+	}
+
+	#[must_use]
+	pub fn code(&self) -> &[Bytecode] {
+		&self.code
+	}
+
+	#[must_use]
+	pub fn span(&self, index: usize) -> Range<usize> {
+		self.spans[index].clone()
+	}
+
+	fn compile_into(&mut self, ops: &[BrainOperation]) -> Result<(), InterpreterError> {
+		for op in ops {
+			let bytecode = match *op.op() {
+				BrainOperationType::IncrementCell(CellOffsetOptions { value, offset }) => {
+					Bytecode::Add { value, offset }
+				}
+				BrainOperationType::DecrementCell(CellOffsetOptions { value, offset }) => {
+					Bytecode::Sub { value, offset }
+				}
+				BrainOperationType::SetCell(CellOffsetOptions { value, offset }) => {
+					Bytecode::Set { value, offset }
+				}
+				BrainOperationType::MovePointer(offset) => Bytecode::Move(offset),
+				BrainOperationType::MoveCellValue(CellOffsetOptions { value, offset }) => {
+					Bytecode::MoveValue {
+						factor: value,
+						offset,
+					}
+				}
+				BrainOperationType::TakeCellValue(CellOffsetOptions { value, offset }) => {
+					Bytecode::TakeValue {
+						factor: value,
+						offset,
+					}
+				}
+				BrainOperationType::InputIntoCell => Bytecode::Input,
+				BrainOperationType::OutputCell(CellOffsetOptions { value, offset }) => {
+					Bytecode::Output { value, offset }
+				}
+				BrainOperationType::OutputValue(value) => Bytecode::OutputValue(value),
+				BrainOperationType::DynamicLoop(ref ops) => {
+					let start = self.code.len();
+
+					self.push(Bytecode::JumpIfZero(usize::MAX), op.span());
+					self.compile_into(ops)?;
+					self.push(Bytecode::JumpUnlessZero(start + 1), op.span());
+
+					self.code[start] = Bytecode::JumpIfZero(self.code.len());
+
+					continue;
+				}
+				BrainOperationType::Comment(..) => continue,
+				ref op => return Err(InterpreterError::NotImplemented(op.clone())),
+			};
+
+			self.push(bytecode, op.span());
+		}
+
+		Ok(())
+	}
+
+	fn push(&mut self, bytecode: Bytecode, span: Range<usize>) {
+		self.code.push(bytecode);
+		self.spans.push(span);
+	}
+}
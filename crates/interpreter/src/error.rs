@@ -0,0 +1,68 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+	io::Error as IoError,
+	ops::Range,
+};
+
+use frick_interop::StopReason;
+use frick_operations::BrainOperationType;
+
+#[derive(Debug)]
+pub enum InterpreterError {
+	Io(IoError),
+	NotImplemented(BrainOperationType),
+	OutOfBounds { pointer: isize, span: Range<usize> },
+	CellOverflow { underflow: bool, span: Range<usize> },
+	Stopped(StopReason),
+}
+
+impl Display for InterpreterError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Io(..) => f.write_str("an IO error has occurred"),
+			Self::NotImplemented(op) => {
+				f.write_str("operation ")?;
+				Debug::fmt(&op, f)?;
+				f.write_str(" is not implemented")
+			}
+			Self::OutOfBounds { pointer, span } => {
+				f.write_str("tape pointer moved out of bounds to ")?;
+				Display::fmt(&pointer, f)?;
+				f.write_str(" at offset ")?;
+				Display::fmt(&span.start, f)
+			}
+			Self::CellOverflow { underflow, span } => {
+				f.write_str(if *underflow {
+					"cell decremented below zero"
+				} else {
+					"cell incremented past its maximum value"
+				})?;
+				f.write_str(" at offset ")?;
+				Display::fmt(&span.start, f)
+			}
+			Self::Stopped(reason) => {
+				f.write_str("program was stopped: ")?;
+				Display::fmt(&reason, f)
+			}
+		}
+	}
+}
+
+impl StdError for InterpreterError {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		match self {
+			Self::Io(e) => Some(e),
+			Self::NotImplemented(..)
+			| Self::OutOfBounds { .. }
+			| Self::CellOverflow { .. }
+			| Self::Stopped(..) => None,
+		}
+	}
+}
+
+impl From<IoError> for InterpreterError {
+	fn from(value: IoError) -> Self {
+		Self::Io(value)
+	}
+}
@@ -0,0 +1,317 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod bytecode;
+mod error;
+mod machine;
+
+use std::io::prelude::*;
+
+use frick_interop::TapeSnapshot;
+use frick_operations::BrainOperation;
+use frick_spec::Spec;
+
+use self::machine::Machine;
+pub use self::{
+	bytecode::{Bytecode, Program},
+	error::InterpreterError,
+};
+
+/// Runs optimized operations directly, without going through LLVM.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+	program: Program,
+	spec: Spec,
+}
+
+impl Interpreter {
+	pub fn new(ops: &[BrainOperation], spec: Spec) -> Result<Self, InterpreterError> {
+		Ok(Self {
+			program: Program::compile(ops)?,
+			spec,
+		})
+	}
+
+	#[must_use]
+	pub const fn program(&self) -> &Program {
+		&self.program
+	}
+
+	#[must_use]
+	pub const fn spec(&self) -> Spec {
+		self.spec
+	}
+
+	/// Runs the program to completion, returning the tape and pointer as they were at the end.
+	///
+	/// The returned tape only matches the unoptimized program when the operations were optimized
+	/// with strict semantics, as trailing work without output is dropped otherwise.
+	#[tracing::instrument(skip_all)]
+	pub fn run(
+		&self,
+		input: &mut (impl Read + ?Sized),
+		output: &mut (impl Write + ?Sized),
+	) -> Result<TapeSnapshot, InterpreterError> {
+		let mut machine = Machine::new(self.spec, input, output);
+
+		let result = machine.execute(&self.program);
+
+		// whatever was output before an error is still part of the output
+		machine.flush()?;
+
+		result?;
+
+		Ok(machine.into_snapshot())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, path::Path, time::Duration};
+
+	use frick_interop::{StopReason, TapeSnapshot};
+	use frick_optimizer::Optimizer;
+	use frick_spec::{CellOverflow, CellWidth, EofMode, Spec, TapeBoundary};
+
+	use super::{Interpreter, InterpreterError};
+
+	/// Each example with its input, along with the EOF it was written for.
+	const EXAMPLES: &[(&str, &[u8], EofMode)] = &[
+		("666.bf", b"", EofMode::Newline),
+		("a_to_z.bf", b"", EofMode::Newline),
+		("ascii.bf", b"", EofMode::Newline),
+		("bitwise.bf", b"", EofMode::Newline),
+		("bottles.bf", b"", EofMode::Newline),
+		("chars.bf", b"", EofMode::Newline),
+		("collatz.bf", b"27\n97\n", EofMode::Zero),
+		("diamond.bf", b"", EofMode::Newline),
+		("echo.bf", b"x", EofMode::Newline),
+		(
+			"head.bf",
+			b"1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n",
+			EofMode::Newline,
+		),
+		("hello_world.bf", b"", EofMode::Newline),
+		("rot13.bf", b"Hello, World!\n", EofMode::Zero),
+		("serptri.bf", b"", EofMode::Newline),
+		("squares.bf", b"", EofMode::Newline),
+	];
+
+	fn interpret(
+		program: &[u8],
+		spec: Spec,
+		input: &[u8],
+	) -> (Vec<u8>, Result<TapeSnapshot, InterpreterError>) {
+		let ops = Optimizer::optimize_operations(
+			frick_operations::parse_bytes(program).unwrap(),
+			spec,
+			&mut Vec::new(),
+		);
+
+		let mut output = Vec::new();
+		let result = Interpreter::new(&ops, spec)
+			.and_then(|interpreter| interpreter.run(&mut &*input, &mut output));
+
+		(output, result)
+	}
+
+	/// The final tape is only exact with strict semantics.
+	fn final_tape(program: &str, spec: Spec, input: &[u8]) -> TapeSnapshot {
+		interpret(program.as_bytes(), spec.with_strict_semantics(true), input)
+			.1
+			.unwrap()
+	}
+
+	/// Only the cells up to the last non-zero one or the pointer.
+	fn used_cells(snapshot: &TapeSnapshot) -> &[u32] {
+		let used = snapshot
+			.tape()
+			.iter()
+			.rposition(|&cell| !matches!(cell, 0))
+			.map_or(0, |last| last + 1)
+			.max(snapshot.pointer() + 1);
+
+		&snapshot.tape()[..used]
+	}
+
+	#[test]
+	fn examples_match_snapshots() {
+		let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+		for &(name, input, eof) in EXAMPLES {
+			let program = fs::read(root.join("../../programs").join(name)).unwrap();
+			let expected =
+				fs::read(root.join("src/snapshots").join(name).with_extension("txt")).unwrap();
+			let spec = Spec::new().with_eof(eof);
+
+			for spec in [spec, spec.with_strict_semantics(true)] {
+				let (output, result) = interpret(&program, spec, input);
+
+				result.unwrap();
+
+				assert_eq!(
+					String::from_utf8_lossy(&output),
+					String::from_utf8_lossy(&expected),
+					"{name} with {spec:?}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn wrapping_tape() {
+		let spec = Spec::new().with_boundary(TapeBoundary::Wrap);
+
+		let snapshot = final_tape("<+<++", spec, b"");
+
+		assert_eq!(snapshot.pointer(), spec.tape_size() - 2);
+		assert_eq!(snapshot.cell(spec.tape_size() - 1), Some(1));
+		assert_eq!(snapshot.current_cell(), 2);
+	}
+
+	#[test]
+	fn aborting_tape() {
+		let spec = Spec::new()
+			.with_boundary(TapeBoundary::Abort)
+			.with_strict_semantics(true);
+
+		let snapshot = final_tape(">>+<-", spec, b"");
+
+		assert_eq!(snapshot.pointer(), 1);
+		assert_eq!(used_cells(&snapshot), [0, 255, 1]);
+
+		assert!(matches!(
+			interpret(b"+>+<<", spec, b"").1,
+			Err(InterpreterError::OutOfBounds { pointer: -1, .. })
+		));
+
+		let past_the_end = ">".repeat(spec.tape_size());
+
+		assert!(matches!(
+			interpret(past_the_end.as_bytes(), spec, b"").1,
+			Err(InterpreterError::OutOfBounds { .. })
+		));
+	}
+
+	#[test]
+	fn leaving_the_tape_and_coming_back_aborts() {
+		let spec = Spec::new().with_boundary(TapeBoundary::Abort);
+
+		for (program, span) in [(&b"<>."[..], 0..1), (b"+<+>>.", 1..3)] {
+			assert!(
+				matches!(
+					interpret(program, spec, b"").1,
+					Err(InterpreterError::OutOfBounds { pointer: -1, span: s }) if s == span
+				),
+				"{}",
+				program.escape_ascii()
+			);
+		}
+	}
+
+	#[test]
+	fn growing_tape() {
+		let spec = Spec::new()
+			.with_boundary(TapeBoundary::Grow)
+			.with_strict_semantics(true)
+			.with_tape_size(16)
+			.unwrap();
+
+		let program = format!("{}+>++", ">".repeat(40));
+		let snapshot = final_tape(&program, spec, b"");
+
+		assert_eq!(snapshot.pointer(), 41);
+		assert_eq!(snapshot.cell(40), Some(1));
+		assert_eq!(snapshot.current_cell(), 2);
+
+		assert!(matches!(
+			interpret(b"+<", spec, b"").1,
+			Err(InterpreterError::OutOfBounds { pointer: -1, span }) if span == (1..2)
+		));
+	}
+
+	#[test]
+	fn every_eof_mode() {
+		for width in [CellWidth::U8, CellWidth::U16, CellWidth::U32] {
+			for (eof, expected) in [
+				(EofMode::Zero, 0),
+				(EofMode::Max, width.max()),
+				(EofMode::Newline, u32::from(b'\n')),
+				(EofMode::Unchanged, 7),
+			] {
+				let spec = Spec::new().with_cell_width(width).with_eof(eof);
+
+				let snapshot = final_tape("+++++++,.", spec, b"");
+
+				assert_eq!(snapshot.current_cell(), expected, "{eof:?} with {width:?}");
+			}
+
+			let snapshot = final_tape(",>,", Spec::new().with_cell_width(width), b"a");
+
+			assert_eq!(used_cells(&snapshot), [u32::from(b'a'), u32::from(b'\n')]);
+		}
+	}
+
+	#[test]
+	fn every_cell_width() {
+		for width in [CellWidth::U8, CellWidth::U16, CellWidth::U32] {
+			let spec = Spec::new().with_cell_width(width);
+
+			let snapshot = final_tape("->+++[-<+>]<.", spec, b"");
+
+			assert_eq!(snapshot.current_cell(), 2, "{width:?}");
+
+			let snapshot = final_tape("-[->+<]>+", spec, b"");
+
+			assert_eq!(snapshot.current_cell(), 0, "{width:?}");
+			assert_eq!(
+				used_cells(&snapshot),
+				[0, 0],
+				"{width:?} should wrap back to zero"
+			);
+		}
+	}
+
+	#[test]
+	fn step_limit() {
+		let spec = Spec::new()
+			.with_strict_semantics(true)
+			.with_step_limit(1000);
+
+		assert!(matches!(
+			interpret(b"+[>+<]", spec, b"").1,
+			Err(InterpreterError::Stopped(StopReason::StepLimit(1000)))
+		));
+	}
+
+	#[test]
+	fn timeout() {
+		let timeout = Duration::from_millis(20);
+		let spec = Spec::new()
+			.with_strict_semantics(true)
+			.with_timeout(timeout);
+
+		assert!(matches!(
+			interpret(b"+[>+<]", spec, b"").1,
+			Err(InterpreterError::Stopped(StopReason::Timeout(t))) if t == timeout
+		));
+	}
+	#[test]
+	fn increment_past_max_traps() {
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		assert!(matches!(
+			interpret(&b"+".repeat(256), spec, b"").1,
+			Err(InterpreterError::CellOverflow { underflow: false, span }) if span == (255..256)
+		));
+	}
+
+	#[test]
+	fn decrement_below_zero_traps() {
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		assert!(matches!(
+			interpret(b"+>-", spec, b"").1,
+			Err(InterpreterError::CellOverflow { underflow: true, span }) if span == (2..3)
+		));
+	}
+}
@@ -0,0 +1,220 @@
+use std::{
+	io::{self, prelude::*},
+	time::Instant,
+};
+
+use frick_interop::{StopReason, TapeSnapshot};
+use frick_spec::{Spec, TapeBoundary};
+
+use super::{Bytecode, InterpreterError, Program};
+
+/// How many loop iterations run between checks of the timeout.
+const TIMEOUT_CHECK_INTERVAL: u64 = 0x10000;
+
+pub struct Machine<'io, R: ?Sized, W: ?Sized> {
+	spec: Spec,
+	tape: Vec<u32>,
+	pointer: usize,
+	input: &'io mut R,
+	output: &'io mut W,
+	buffer: Vec<u8>,
+	steps: u64,
+	started: Instant,
+}
+
+impl<'io, R, W> Machine<'io, R, W>
+where
+	R: Read + ?Sized,
+	W: Write + ?Sized,
+{
+	pub fn new(spec: Spec, input: &'io mut R, output: &'io mut W) -> Self {
+		Self {
+			spec,
+			tape: vec![0; spec.tape_size()],
+			pointer: 0,
+			input,
+			output,
+			buffer: Vec::with_capacity(spec.output_buffer_size()),
+			steps: 0,
+			started: Instant::now(),
+		}
+	}
+
+	pub fn execute(&mut self, program: &Program) -> Result<(), InterpreterError> {
+		let code = program.code();
+		let mut pc = 0;
+
+		while let Some(&bytecode) = code.get(pc) {
+			let out_of_bounds = |pointer| InterpreterError::OutOfBounds {
+				pointer,
+				span: program.span(pc),
+			};
+			let overflow = |underflow| InterpreterError::CellOverflow {
+				underflow,
+				span: program.span(pc),
+			};
+
+			match bytecode {
+				Bytecode::Add { value, offset } => {
+					let index = self.cell_index(offset).map_err(out_of_bounds)?;
+
+					self.tape[index] = self
+						.spec
+						.overflow()
+						.add(self.spec.cell_width(), self.tape[index], value)
+						.ok_or_else(|| overflow(false))?;
+				}
+				Bytecode::Sub { value, offset } => {
+					let index = self.cell_index(offset).map_err(out_of_bounds)?;
+
+					self.tape[index] = self
+						.spec
+						.overflow()
+						.sub(self.spec.cell_width(), self.tape[index], value)
+						.ok_or_else(|| overflow(true))?;
+				}
+				Bytecode::Set { value, offset } => {
+					let index = self.cell_index(offset).map_err(out_of_bounds)?;
+
+					self.tape[index] = value;
+				}
+				Bytecode::Move(offset) => {
+					self.pointer = self.cell_index(offset).map_err(out_of_bounds)?;
+				}
+				Bytecode::MoveValue { factor, offset } => {
+					self.move_value(factor, offset).map_err(out_of_bounds)?;
+				}
+				Bytecode::TakeValue { factor, offset } => {
+					self.pointer = self.move_value(factor, offset).map_err(out_of_bounds)?;
+				}
+				Bytecode::Input => self.input()?,
+				Bytecode::Output { value, offset } => {
+					let index = self.cell_index(offset).map_err(out_of_bounds)?;
+
+					// only the low byte of a cell is ever output
+					self.output(self.tape[index].wrapping_add(value) as u8)?;
+				}
+				Bytecode::OutputValue(value) => self.output(value)?,
+				Bytecode::JumpIfZero(target) => {
+					if matches!(self.tape[self.pointer], 0) {
+						pc = target;
+						continue;
+					}
+				}
+				Bytecode::JumpUnlessZero(target) => {
+					if !matches!(self.tape[self.pointer], 0) {
+						self.step()?;
+
+						pc = target;
+						continue;
+					}
+				}
+			}
+
+			pc += 1;
+		}
+
+		Ok(())
+	}
+
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.output.write_all(&self.buffer)?;
+		self.buffer.clear();
+
+		self.output.flush()
+	}
+
+	pub fn into_snapshot(self) -> TapeSnapshot {
+		TapeSnapshot::new(self.tape, self.pointer)
+	}
+
+	/// Resolves the cell `offset` away from the pointer, returning the raw pointer when it is out
+	/// of bounds.
+	fn cell_index(&mut self, offset: i32) -> Result<usize, isize> {
+		let target = (self.pointer as isize).wrapping_add(offset as isize);
+
+		match self.spec.boundary() {
+			TapeBoundary::Wrap => Ok(target as usize & (self.tape.len() - 1)),
+			TapeBoundary::Abort => usize::try_from(target)
+				.ok()
+				.filter(|index| *index < self.tape.len())
+				.ok_or(target),
+			TapeBoundary::Grow => {
+				let index = usize::try_from(target).map_err(|_| target)?;
+
+				if index >= self.tape.len() {
+					let new_len = index
+						.checked_add(1)
+						.and_then(usize::checked_next_power_of_two)
+						.ok_or(target)?;
+
+					self.tape.resize(new_len, 0);
+				}
+
+				Ok(index)
+			}
+		}
+	}
+
+	/// Returns the index of the cell the value was moved into.
+	fn move_value(&mut self, factor: u32, offset: i32) -> Result<usize, isize> {
+		let value = self.tape[self.pointer];
+		self.tape[self.pointer] = 0;
+
+		let index = self.cell_index(offset)?;
+
+		let width = self.spec.cell_width();
+
+		self.tape[index] = width.wrapping_add(self.tape[index], width.wrapping_mul(value, factor));
+
+		Ok(index)
+	}
+
+	fn input(&mut self) -> Result<(), InterpreterError> {
+		// programs are usually prompting for whatever comes next
+		self.flush()?;
+
+		let mut value = 0;
+
+		let read = match self.input.read_exact(std::slice::from_mut(&mut value)) {
+			Ok(()) => Some(value),
+			Err(e) if matches!(e.kind(), io::ErrorKind::UnexpectedEof) => None,
+			Err(e) => return Err(e.into()),
+		};
+
+		if let Some(value) = read.map(u32::from).or_else(|| self.spec.eof_value()) {
+			self.tape[self.pointer] = value;
+		}
+
+		Ok(())
+	}
+
+	fn output(&mut self, value: u8) -> io::Result<()> {
+		self.buffer.push(value);
+
+		if self.buffer.len() >= self.spec.output_buffer_size() {
+			self.flush()?;
+		}
+
+		Ok(())
+	}
+
+	fn step(&mut self) -> Result<(), InterpreterError> {
+		self.steps += 1;
+
+		if let Some(step_limit) = self.spec.step_limit()
+			&& self.steps > step_limit
+		{
+			return Err(InterpreterError::Stopped(StopReason::StepLimit(step_limit)));
+		}
+
+		if let Some(timeout) = self.spec.timeout()
+			&& matches!(self.steps % TIMEOUT_CHECK_INTERVAL, 1)
+			&& self.started.elapsed() >= timeout
+		{
+			return Err(InterpreterError::Stopped(StopReason::Timeout(timeout)));
+		}
+
+		Ok(())
+	}
+}
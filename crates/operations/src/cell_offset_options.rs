@@ -2,18 +2,19 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CellOffsetOptions {
-	pub value: u8,
+	/// Always fits in the spec's cell width once optimized.
+	pub value: u32,
 	pub offset: i32,
 }
 
 impl CellOffsetOptions {
 	#[must_use]
-	pub const fn new(value: u8, offset: i32) -> Self {
+	pub const fn new(value: u32, offset: i32) -> Self {
 		Self { value, offset }
 	}
 
 	#[must_use]
-	pub const fn value(self) -> u8 {
+	pub const fn value(self) -> u32 {
 		self.value
 	}
 
@@ -23,7 +24,7 @@ impl CellOffsetOptions {
 	}
 
 	#[must_use]
-	pub const fn into_parts(self) -> (u8, i32) {
+	pub const fn into_parts(self) -> (u32, i32) {
 		(self.value, self.offset)
 	}
 }
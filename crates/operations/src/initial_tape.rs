@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+
+use frick_utils::IntoIteratorExt as _;
+
+use super::{BrainOperation, BrainOperationType};
+
+/// The state of the tape when a program starts running.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InitialTape {
+	cells: Vec<u32>,
+	pointer: usize,
+}
+
+impl InitialTape {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			cells: Vec::new(),
+			pointer: 0,
+		}
+	}
+
+	/// Cells are placed starting from the first cell of the tape, not the pointer.
+	#[must_use]
+	pub fn with_cells(self, cells: impl IntoIterator<Item = u32>) -> Self {
+		Self {
+			cells: cells.collect_to(),
+			..self
+		}
+	}
+
+	#[must_use]
+	pub fn with_pointer(self, pointer: usize) -> Self {
+		Self { pointer, ..self }
+	}
+
+	#[must_use]
+	pub fn cells(&self) -> &[u32] {
+		&self.cells
+	}
+
+	#[must_use]
+	pub const fn pointer(&self) -> usize {
+		self.pointer
+	}
+
+	/// The fewest cells a tape needs to hold every initial cell and the pointer.
+	#[must_use]
+	pub fn min_tape_size(&self) -> usize {
+		self.cells.len().max(self.pointer + 1)
+	}
+
+	/// The operations that bring a zeroed tape with the pointer on the first cell into this state.
+	///
+	/// Prepending these to a program keeps everything after parsing free to assume that's how
+	/// every program starts.
+	#[must_use]
+	pub fn prologue(&self) -> Vec<BrainOperation> {
+		let mut ops = self
+			.cells
+			.iter()
+			.enumerate()
+			.filter(|(_, value)| !matches!(value, 0))
+			.map(|(index, &value)| {
+				BrainOperation::new(BrainOperationType::set_cell_at(value, index as i32), 0..0)
+			})
+			.collect::<Vec<_>>();
+
+		if !matches!(self.pointer, 0) {
+			ops.push(BrainOperation::move_pointer(self.pointer as i32, 0..0));
+		}
+
+		ops
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use super::{BrainOperation, BrainOperationType, InitialTape};
+
+	#[test]
+	fn empty_tape_has_no_prologue() {
+		assert!(InitialTape::new().prologue().is_empty());
+		assert!(InitialTape::new().with_cells([0, 0]).prologue().is_empty());
+	}
+
+	#[test]
+	fn prologue_sets_non_zero_cells_then_moves() {
+		let prologue = InitialTape::new()
+			.with_cells([3, 0, 7])
+			.with_pointer(4)
+			.prologue();
+
+		assert_eq!(
+			prologue,
+			[
+				BrainOperation::new(BrainOperationType::set_cell_at(3, 0), 0..0),
+				BrainOperation::new(BrainOperationType::set_cell_at(7, 2), 0..0),
+				BrainOperation::move_pointer(4, 0..0),
+			]
+		);
+	}
+
+	#[test]
+	fn min_tape_size_covers_the_pointer() {
+		assert_eq!(InitialTape::new().min_tape_size(), 1);
+		assert_eq!(InitialTape::new().with_cells([1, 2, 3]).min_tape_size(), 3);
+		assert_eq!(
+			InitialTape::new()
+				.with_cells([1, 2, 3])
+				.with_pointer(5)
+				.min_tape_size(),
+			6
+		);
+		assert_eq!(
+			InitialTape::new()
+				.with_cells(Vec::new())
+				.with_pointer(2)
+				.min_tape_size(),
+			3
+		);
+	}
+}
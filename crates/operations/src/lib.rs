@@ -7,6 +7,7 @@ extern crate alloc;
 extern crate std;
 
 mod cell_offset_options;
+mod initial_tape;
 #[cfg(feature = "parse")]
 mod parse;
 
@@ -16,9 +17,9 @@ use core::ops::{Deref, DerefMut, Range};
 use frick_utils::IntoIteratorExt as _;
 use serde::{Deserialize, Serialize};
 
-pub use self::cell_offset_options::*;
 #[cfg(feature = "parse")]
 pub use self::parse::*;
+pub use self::{cell_offset_options::*, initial_tape::*};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -127,32 +128,32 @@ impl BrainOperationType {
 	}
 
 	#[must_use]
-	pub const fn increment_cell(value: u8) -> Self {
+	pub const fn increment_cell(value: u32) -> Self {
 		Self::increment_cell_at(value, 0)
 	}
 
 	#[must_use]
-	pub const fn increment_cell_at(value: u8, offset: i32) -> Self {
+	pub const fn increment_cell_at(value: u32, offset: i32) -> Self {
 		Self::IncrementCell(CellOffsetOptions::new(value, offset))
 	}
 
 	#[must_use]
-	pub const fn decrement_cell(value: u8) -> Self {
+	pub const fn decrement_cell(value: u32) -> Self {
 		Self::decrement_cell_at(value, 0)
 	}
 
 	#[must_use]
-	pub const fn decrement_cell_at(value: u8, offset: i32) -> Self {
+	pub const fn decrement_cell_at(value: u32, offset: i32) -> Self {
 		Self::DecrementCell(CellOffsetOptions::new(value, offset))
 	}
 
 	#[must_use]
-	pub const fn set_cell(value: u8) -> Self {
+	pub const fn set_cell(value: u32) -> Self {
 		Self::set_cell_at(value, 0)
 	}
 
 	#[must_use]
-	pub const fn set_cell_at(value: u8, offset: i32) -> Self {
+	pub const fn set_cell_at(value: u32, offset: i32) -> Self {
 		Self::SetCell(CellOffsetOptions::new(value, offset))
 	}
 
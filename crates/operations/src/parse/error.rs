@@ -0,0 +1,178 @@
+use alloc::{borrow::ToOwned as _, string::String, vec::Vec};
+use core::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	ops::Range,
+};
+use std::{
+	error::Error as StdError,
+	io::{self, Error as IoError},
+};
+
+use ariadne::{IndexType, Label, Report, ReportKind, Source};
+
+#[derive(Debug)]
+pub enum ParseError {
+	Io(IoError),
+	Syntax(Vec<SyntaxError>),
+}
+
+impl ParseError {
+	pub fn report(&self, name: &str, source: &str) -> io::Result<()> {
+		let Self::Syntax(errors) = self else {
+			return Ok(());
+		};
+
+		for error in errors {
+			let report = error.to_report(name);
+
+			let cache = (name.to_owned(), Source::from(source));
+
+			if let Some(indicatif_writer) = tracing_indicatif::writer::get_indicatif_stderr_writer()
+			{
+				report.write(cache, indicatif_writer)?;
+			} else {
+				report.eprint(cache)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Io(..) => f.write_str("an IO error occurred while reading the program"),
+			Self::Syntax(errors) => {
+				Display::fmt(&errors.len(), f)?;
+				f.write_str(" syntax error(s) were found")
+			}
+		}
+	}
+}
+
+impl StdError for ParseError {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		match self {
+			Self::Io(e) => Some(e),
+			Self::Syntax(..) => None,
+		}
+	}
+}
+
+impl From<IoError> for ParseError {
+	fn from(value: IoError) -> Self {
+		Self::Io(value)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxError {
+	UnmatchedOpen {
+		open: Range<usize>,
+		expected_close: Range<usize>,
+	},
+	UnmatchedClose(Range<usize>),
+	Other {
+		message: String,
+		span: Range<usize>,
+	},
+}
+
+impl SyntaxError {
+	#[must_use]
+	pub fn span(&self) -> Range<usize> {
+		match self {
+			Self::UnmatchedOpen { open, .. } => open.clone(),
+			Self::UnmatchedClose(span) | Self::Other { span, .. } => span.clone(),
+		}
+	}
+
+	#[must_use]
+	pub const fn start(&self) -> usize {
+		match self {
+			Self::UnmatchedOpen { open, .. } => open.start,
+			Self::UnmatchedClose(span) | Self::Other { span, .. } => span.start,
+		}
+	}
+
+	fn to_report(&self, name: &str) -> Report<'static, (String, Range<usize>)> {
+		let span = |range: &Range<usize>| (name.to_owned(), range.clone());
+
+		let report = Report::build(ReportKind::Error, span(&self.span()))
+			.with_config(ariadne::Config::new().with_index_type(IndexType::Byte))
+			.with_message(self);
+
+		match self {
+			Self::UnmatchedOpen {
+				open,
+				expected_close,
+			} => report
+				.with_label(Label::new(span(open)).with_message("loop opened here"))
+				.with_label(
+					Label::new(span(expected_close)).with_message("expected `]` to close the loop"),
+				),
+			Self::UnmatchedClose(close) => {
+				report.with_label(Label::new(span(close)).with_message("no `[` opens this loop"))
+			}
+			Self::Other { span: range, .. } => report.with_label(Label::new(span(range))),
+		}
+		.finish()
+	}
+}
+
+impl Display for SyntaxError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::UnmatchedOpen { .. } => f.write_str("unmatched `[`"),
+			Self::UnmatchedClose(..) => f.write_str("unmatched `]`"),
+			Self::Other { message, .. } => f.write_str(message),
+		}
+	}
+}
+
+impl StdError for SyntaxError {}
+
+#[cfg(test)]
+mod tests {
+	use alloc::{borrow::ToOwned as _, string::String, vec::Vec};
+
+	use super::SyntaxError;
+
+	fn render(error: &SyntaxError, source: &str) -> String {
+		let mut output = Vec::new();
+
+		error
+			.to_report("test.bf")
+			.write(
+				("test.bf".to_owned(), ariadne::Source::from(source)),
+				&mut output,
+			)
+			.unwrap();
+
+		String::from_utf8(output).unwrap()
+	}
+
+	#[test]
+	fn reports_expected_close_at_end_of_source() {
+		let report = render(
+			&SyntaxError::UnmatchedOpen {
+				open: 1..2,
+				expected_close: 3..3,
+			},
+			"+[-",
+		);
+
+		assert!(report.contains("unmatched `[`"));
+		assert!(report.contains("loop opened here"));
+		assert!(report.contains("expected `]` to close the loop"));
+	}
+
+	#[test]
+	fn reports_stray_close() {
+		let report = render(&SyntaxError::UnmatchedClose(1..2), "+]");
+
+		assert!(report.contains("unmatched `]`"));
+		assert!(report.contains("no `[` opens this loop"));
+	}
+}
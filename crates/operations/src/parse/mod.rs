@@ -0,0 +1,188 @@
+mod error;
+
+use alloc::{string::ToString as _, vec::Vec};
+use std::{fs, io::Read, path::Path};
+
+use chumsky::{
+	input::{Stream, ValueInput},
+	prelude::*,
+};
+
+pub use self::error::*;
+use crate::{BrainOperation, BrainOperationType, CellOffsetOptions};
+
+pub fn parse(file_path: impl AsRef<Path>) -> Result<Vec<BrainOperation>, ParseError> {
+	parse_reader(fs::File::open(file_path)?)
+}
+
+#[tracing::instrument("parse code", skip_all)]
+pub fn parse_str(source: &str) -> Result<Vec<BrainOperation>, ParseError> {
+	check_brackets(source.char_indices(), source.len())?;
+
+	parser().parse(source).into_result().map_err(syntax_errors)
+}
+
+// Every byte is treated as a single character, so spans are byte offsets into `source`
+#[tracing::instrument("parse code", skip_all)]
+pub fn parse_bytes(source: &[u8]) -> Result<Vec<BrainOperation>, ParseError> {
+	let chars = || source.iter().copied().map(char::from);
+
+	check_brackets(chars().enumerate(), source.len())?;
+
+	parser()
+		.parse(Stream::from_iter(chars()))
+		.into_result()
+		.map_err(syntax_errors)
+}
+
+pub fn parse_reader(mut reader: impl Read) -> Result<Vec<BrainOperation>, ParseError> {
+	let mut source = Vec::new();
+
+	reader.read_to_end(&mut source)?;
+
+	parse_bytes(&source)
+}
+
+// Run before the parser proper so every unmatched bracket is reported with both ends, rather than
+// only the first one chumsky gives up on
+fn check_brackets(
+	chars: impl IntoIterator<Item = (usize, char)>,
+	len: usize,
+) -> Result<(), ParseError> {
+	let mut open_brackets = Vec::new();
+	let mut errors = Vec::new();
+
+	for (i, c) in chars {
+		match c {
+			'[' => open_brackets.push(i),
+			']' if open_brackets.pop().is_none() => {
+				errors.push(SyntaxError::UnmatchedClose(i..i + 1));
+			}
+			_ => {}
+		}
+	}
+
+	errors.extend(
+		open_brackets
+			.into_iter()
+			.map(|i| SyntaxError::UnmatchedOpen {
+				open: i..i + 1,
+				expected_close: len..len,
+			}),
+	);
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		errors.sort_by_key(SyntaxError::start);
+
+		Err(ParseError::Syntax(errors))
+	}
+}
+
+fn syntax_errors(errs: Vec<Rich<'_, char>>) -> ParseError {
+	ParseError::Syntax(
+		errs.into_iter()
+			.map(|err| SyntaxError::Other {
+				message: err.to_string(),
+				span: err.span().into_range(),
+			})
+			.collect(),
+	)
+}
+
+fn parser<'src, I>() -> impl Parser<'src, I, Vec<BrainOperation>, extra::Err<Rich<'src, char>>>
+where
+	I: ValueInput<'src, Token = char, Span = SimpleSpan>,
+{
+	recursive(|expr| {
+		choice((
+			just('+').to(BrainOperationType::increment_cell(1)),
+			just('-').to(BrainOperationType::decrement_cell(1)),
+			just('<').to(BrainOperationType::MovePointer(-1)),
+			just('>').to(BrainOperationType::MovePointer(1)),
+			just('.').to(BrainOperationType::OutputCell(CellOffsetOptions::default())),
+			just(',').to(BrainOperationType::InputIntoCell),
+			none_of("+-<>.,[]").map(BrainOperationType::Comment),
+		))
+		.or(expr
+			.delimited_by(
+				just('[').labelled("start loop"),
+				just(']').labelled("end loop"),
+			)
+			.map(BrainOperationType::DynamicLoop))
+		.map_with(|e, t| {
+			BrainOperation::new(e, {
+				let span: SimpleSpan = t.span();
+
+				span.into_range()
+			})
+		})
+		.repeated()
+		.collect()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::{vec, vec::Vec};
+
+	use super::{ParseError, SyntaxError, parse_bytes, parse_str};
+
+	fn syntax_errors(result: Result<impl Sized, ParseError>) -> Vec<SyntaxError> {
+		match result {
+			Err(ParseError::Syntax(errors)) => errors,
+			Err(ParseError::Io(e)) => panic!("unexpected IO error: {e}"),
+			Ok(..) => panic!("program parsed without errors"),
+		}
+	}
+
+	#[test]
+	fn unmatched_open_expects_close_at_end() {
+		let errors = syntax_errors(parse_str("+[->+<"));
+
+		assert_eq!(
+			errors,
+			[SyntaxError::UnmatchedOpen {
+				open: 1..2,
+				expected_close: 6..6,
+			}]
+		);
+	}
+
+	#[test]
+	fn stray_close() {
+		let errors = syntax_errors(parse_str("+]-"));
+
+		assert_eq!(errors, [SyntaxError::UnmatchedClose(1..2)]);
+	}
+
+	#[test]
+	fn unmatched_brackets_are_sorted() {
+		let errors = syntax_errors(parse_str("][[]"));
+
+		assert_eq!(
+			errors,
+			vec![
+				SyntaxError::UnmatchedClose(0..1),
+				SyntaxError::UnmatchedOpen {
+					open: 1..2,
+					expected_close: 4..4,
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn byte_spans_skip_non_utf8_comments() {
+		let errors = syntax_errors(parse_bytes(b"\xff\xfe[+"));
+
+		assert_eq!(
+			errors,
+			[SyntaxError::UnmatchedOpen {
+				open: 2..3,
+				expected_close: 4..4,
+			}]
+		);
+	}
+}
@@ -0,0 +1,196 @@
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	io,
+	ops::Range,
+};
+
+use ariadne::{IndexType, Label, Report, ReportKind, Source};
+
+/// Something the optimizer proved about the program that's likely a bug in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+	/// A loop that can't exit once entered.
+	InfiniteLoop(Range<usize>),
+	/// A loop that only reads input, and can't exit once input runs out.
+	InfiniteInputLoop(Range<usize>),
+}
+
+impl Diagnostic {
+	#[must_use]
+	pub fn span(&self) -> Range<usize> {
+		match self {
+			Self::InfiniteLoop(span) | Self::InfiniteInputLoop(span) => span.clone(),
+		}
+	}
+
+	/// Prints the diagnostic as a warning, or as an error if `deny` is set.
+	pub fn report(&self, name: &str, source: &str, deny: bool) -> io::Result<()> {
+		let report = self.to_report(name, deny);
+
+		let cache = (name.to_owned(), Source::from(source));
+
+		if let Some(indicatif_writer) = tracing_indicatif::writer::get_indicatif_stderr_writer() {
+			report.write(cache, indicatif_writer)
+		} else {
+			report.eprint(cache)
+		}
+	}
+
+	fn to_report(&self, name: &str, deny: bool) -> Report<'static, (String, Range<usize>)> {
+		let kind = if deny {
+			ReportKind::Error
+		} else {
+			ReportKind::Warning
+		};
+
+		let span = (name.to_owned(), self.span());
+
+		let report = Report::build(kind, span.clone())
+			.with_config(ariadne::Config::new().with_index_type(IndexType::Byte))
+			.with_message(self);
+
+		match self {
+			Self::InfiniteLoop(..) => report.with_label(
+				Label::new(span).with_message("this loop never terminates once entered"),
+			),
+			Self::InfiniteInputLoop(..) => report.with_label(
+				Label::new(span).with_message("this loop never terminates once input runs out"),
+			),
+		}
+		.finish()
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::InfiniteLoop(..) | Self::InfiniteInputLoop(..) => f.write_str("infinite loop"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use frick_operations::{BrainOperation, BrainOperationType, InitialTape};
+	use frick_spec::{EofMode, Spec};
+
+	use super::Diagnostic;
+	use crate::Optimizer;
+
+	fn diagnose(program: &str, spec: Spec) -> Vec<Diagnostic> {
+		let mut diagnostics = Vec::new();
+
+		Optimizer::optimize_operations(
+			frick_operations::parse_str(program).unwrap(),
+			spec,
+			&mut diagnostics,
+		);
+
+		diagnostics
+	}
+
+	fn render(diagnostic: &Diagnostic, source: &str, deny: bool) -> String {
+		let mut output = Vec::new();
+
+		diagnostic
+			.to_report("test.bf", deny)
+			.write(
+				("test.bf".to_owned(), ariadne::Source::from(source)),
+				&mut output,
+			)
+			.unwrap();
+
+		String::from_utf8(output).unwrap()
+	}
+
+	#[test]
+	fn infinite_loop_is_a_warning() {
+		let diagnostics = diagnose(",[[-]+]", Spec::new());
+
+		assert_eq!(diagnostics, [Diagnostic::InfiniteLoop(1..7)]);
+
+		let report = render(&diagnostics[0], ",[[-]+]", false);
+
+		assert!(report.contains("Warning"));
+		assert!(report.contains("this loop never terminates once entered"));
+	}
+
+	#[test]
+	fn infinite_loop_is_an_error_when_denied() {
+		let diagnostics = diagnose(",[[-]+]", Spec::new());
+
+		let report = render(&diagnostics[0], ",[[-]+]", true);
+
+		assert!(report.contains("Error"));
+		assert!(!report.contains("Warning"));
+	}
+
+	#[test]
+	fn infinite_input_loop_is_reported() {
+		for eof in [EofMode::Max, EofMode::Newline, EofMode::Unchanged] {
+			let diagnostics = diagnose(",[,]", Spec::new().with_eof(eof));
+
+			assert_eq!(diagnostics, [Diagnostic::InfiniteInputLoop(1..4)]);
+
+			let warning = render(&diagnostics[0], ",[,]", false);
+			let error = render(&diagnostics[0], ",[,]", true);
+
+			assert!(warning.contains("Warning"));
+			assert!(error.contains("Error"));
+			assert!(warning.contains("this loop never terminates once input runs out"));
+		}
+	}
+
+	#[test]
+	fn input_loop_ends_when_eof_stores_zero() {
+		assert!(diagnose(",[,]", Spec::new().with_eof(EofMode::Zero)).is_empty());
+	}
+
+	#[test]
+	fn skips_loops_outside_the_source() {
+		let mut diagnostics = Vec::new();
+
+		let ops = InitialTape::new()
+			.with_cells([1, 2])
+			.with_pointer(1)
+			.prologue()
+			.into_iter()
+			.chain([BrainOperation::dynamic_loop(
+				[BrainOperation::new(BrainOperationType::set_cell(1), 0..0)],
+				0..0,
+			)]);
+
+		Optimizer::optimize_operations(
+			ops,
+			Spec::new().with_strict_semantics(true),
+			&mut diagnostics,
+		);
+
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn reports_loops_after_the_initial_tape() {
+		let mut diagnostics = Vec::new();
+
+		let ops = InitialTape::new()
+			.with_cells([1])
+			.prologue()
+			.into_iter()
+			.chain(frick_operations::parse_str("[[-]+]").unwrap());
+
+		Optimizer::optimize_operations(ops, Spec::new(), &mut diagnostics);
+
+		assert_eq!(diagnostics, [Diagnostic::InfiniteLoop(0..6)]);
+	}
+
+	#[test]
+	fn strict_semantics_still_reports() {
+		let spec = Spec::new()
+			.with_strict_semantics(true)
+			.with_eof(EofMode::Unchanged);
+
+		assert_eq!(diagnose("[[-]+]", spec), [Diagnostic::InfiniteLoop(0..6)]);
+		assert_eq!(diagnose("[,]", spec), [Diagnostic::InfiniteInputLoop(0..3)]);
+	}
+}
@@ -49,7 +49,8 @@ impl Analyzer for PointerStateAnalyzer {
 				| BrainInstructionType::EndLoop => {
 					self.insert(i, PointerState::default());
 				}
-				BrainInstructionType::CalculateTapeOffset { .. } => {}
+				BrainInstructionType::CalculateTapeOffset { .. }
+				| BrainInstructionType::CheckTapeBounds { .. } => {}
 				instr_ty if !matches!(i, 0) => {
 					let prev_state = self.pointer_state_at(i - 1);
 
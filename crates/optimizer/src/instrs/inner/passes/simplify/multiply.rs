@@ -46,7 +46,7 @@ impl SimplifyMultiplicationPass {
 						changed_any = true;
 					}
 					x => {
-						let new_imm = Immediate::cell(x.ilog2().convert::<u64>());
+						let new_imm = Immediate::new(x.ilog2().convert::<u64>(), imm.size());
 
 						*instrs[i - 1] = BrainInstructionType::StoreImmediateIntoRegister {
 							imm: new_imm,
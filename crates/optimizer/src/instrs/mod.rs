@@ -5,6 +5,7 @@ mod inner;
 mod verify;
 
 use frick_instructions::BrainInstruction;
+use frick_spec::Spec;
 use frick_utils::IntoIteratorExt as _;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -30,14 +31,14 @@ impl InstructionsOptimizer {
 	}
 
 	#[tracing::instrument("optimize instructions", skip(self))]
-	pub fn run(&mut self) -> Result<(), InstructionsOptimizerError> {
+	pub fn run(&mut self, spec: Spec) -> Result<(), InstructionsOptimizerError> {
 		let mut iteration = 0;
 
-		let mut progress = self.run_passes(iteration)?;
+		let mut progress = self.run_passes(iteration, spec)?;
 
 		while progress {
 			iteration += 1;
-			progress = self.run_passes(iteration)?;
+			progress = self.run_passes(iteration, spec)?;
 		}
 
 		info!(iterations = iteration);
@@ -46,12 +47,16 @@ impl InstructionsOptimizer {
 	}
 
 	#[tracing::instrument(skip(self))]
-	fn run_passes(&mut self, iteration: usize) -> Result<bool, InstructionsOptimizerError> {
+	fn run_passes(
+		&mut self,
+		iteration: usize,
+		spec: Spec,
+	) -> Result<bool, InstructionsOptimizerError> {
 		let mut progress = false;
 
 		self.run_each_pass(&mut progress);
 
-		Verifier::run(&self.instrs)?;
+		Verifier::run(&self.instrs, spec)?;
 
 		Ok(progress)
 	}
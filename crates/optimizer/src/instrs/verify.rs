@@ -1,5 +1,5 @@
 use frick_instructions::{BrainInstruction, BrainInstructionType};
-use frick_spec::POINTER_SIZE;
+use frick_spec::{POINTER_SIZE, Spec};
 use frick_types::{RegOrImm, RegisterTypeEnum};
 use rustc_hash::FxHashMap;
 
@@ -8,12 +8,12 @@ use super::InstructionsOptimizerError;
 pub struct Verifier;
 
 impl Verifier {
-	pub fn run(instrs: &[BrainInstruction]) -> Result<(), InstructionsOptimizerError> {
+	pub fn run(instrs: &[BrainInstruction], spec: Spec) -> Result<(), InstructionsOptimizerError> {
 		if !Self::check_loops(instrs) {
 			return Err(InstructionsOptimizerError::LoopsNotValid);
 		}
 
-		Self::check_register_values(instrs)
+		Self::check_register_values(instrs, spec)
 	}
 
 	fn check_loops(instrs: &[BrainInstruction]) -> bool {
@@ -32,7 +32,11 @@ impl Verifier {
 
 	fn check_register_values(
 		instrs: &[BrainInstruction],
+		spec: Spec,
 	) -> Result<(), InstructionsOptimizerError> {
+		let cell_bits = spec.cell_width().bits();
+		let cell = RegisterTypeEnum::Int(Some(cell_bits as usize));
+
 		let mut registers = FxHashMap::default();
 
 		for i in instrs.iter().copied() {
@@ -43,7 +47,7 @@ impl Verifier {
 					output_reg,
 				} => match registers.get(&pointer_reg.index()).copied() {
 					Some(RegisterTypeEnum::Pointer) => {
-						registers.insert(output_reg.index(), RegisterTypeEnum::Int(Some(8)));
+						registers.insert(output_reg.index(), cell);
 					}
 					found => {
 						return Err(InstructionsOptimizerError::RegisterInvalid {
@@ -61,9 +65,8 @@ impl Verifier {
 						registers.get(&value_reg.index()).copied(),
 						registers.get(&pointer_reg.index()).copied(),
 					) {
-						(Some(RegisterTypeEnum::Int(Some(8))), Some(RegisterTypeEnum::Pointer)) => {
-						}
-						(Some(RegisterTypeEnum::Int(Some(8))), found) => {
+						(Some(value), Some(RegisterTypeEnum::Pointer)) if value == cell => {}
+						(Some(value), found) if value == cell => {
 							return Err(InstructionsOptimizerError::RegisterInvalid {
 								register: pointer_reg.index(),
 								expected: RegisterTypeEnum::Pointer,
@@ -73,7 +76,7 @@ impl Verifier {
 						(found, ..) => {
 							return Err(InstructionsOptimizerError::RegisterInvalid {
 								register: value_reg.index(),
-								expected: RegisterTypeEnum::Int(Some(8)),
+								expected: cell,
 								found,
 							});
 						}
@@ -106,6 +109,43 @@ impl Verifier {
 						}
 					}
 				}
+				BrainInstructionType::CheckTapeBounds { tape_pointer_reg } => {
+					match registers.get(&tape_pointer_reg.index()).copied() {
+						Some(RegisterTypeEnum::Int(Some(64))) => {}
+						Some(RegisterTypeEnum::Int(None)) => {
+							tracing::trace!("got an int, expected an int64");
+						}
+						found => {
+							return Err(InstructionsOptimizerError::RegisterInvalid {
+								register: tape_pointer_reg.index(),
+								expected: RegisterTypeEnum::Int(Some(64)),
+								found,
+							});
+						}
+					}
+				}
+				BrainInstructionType::CheckCellOverflow {
+					cell_reg,
+					result_reg,
+					..
+				} => {
+					for reg in [cell_reg, result_reg] {
+						match registers.get(&reg.index()).copied() {
+							Some(value) if value == cell => {}
+							// the result of the arithmetic being checked
+							Some(RegisterTypeEnum::Int(None)) if reg == result_reg => {
+								tracing::trace!("got an int, expected an int{cell_bits}");
+							}
+							found => {
+								return Err(InstructionsOptimizerError::RegisterInvalid {
+									register: reg.index(),
+									expected: cell,
+									found,
+								});
+							}
+						}
+					}
+				}
 				BrainInstructionType::CalculateTapeOffset {
 					tape_pointer_reg,
 					output_reg,
@@ -203,18 +243,30 @@ impl Verifier {
 					}
 				},
 				BrainInstructionType::InputIntoRegister { output_reg } => {
-					registers.insert(output_reg.index(), RegisterTypeEnum::Int(Some(8)));
+					registers.insert(output_reg.index(), cell);
+				}
+				BrainInstructionType::InputIntoCell { pointer_reg } => {
+					match registers.get(&pointer_reg.index()).copied() {
+						Some(RegisterTypeEnum::Pointer) => {}
+						found => {
+							return Err(InstructionsOptimizerError::RegisterInvalid {
+								register: pointer_reg.index(),
+								expected: RegisterTypeEnum::Pointer,
+								found,
+							});
+						}
+					}
 				}
 				BrainInstructionType::OutputFromRegister { input_reg } => {
 					match registers.get(&input_reg.index()).copied() {
-						Some(RegisterTypeEnum::Int(Some(8))) => {}
+						Some(value) if value == cell => {}
 						Some(RegisterTypeEnum::Int(None)) => {
-							tracing::trace!("got an int, expected an int8");
+							tracing::trace!("got an int, expected an int{cell_bits}");
 						}
 						found => {
 							return Err(InstructionsOptimizerError::RegisterInvalid {
 								register: input_reg.index(),
-								expected: RegisterTypeEnum::Int(Some(8)),
+								expected: cell,
 								found,
 							});
 						}
@@ -228,23 +280,20 @@ impl Verifier {
 					registers.get(&lhs_reg.index()).copied(),
 					registers.get(&rhs_reg.index()).copied(),
 				) {
-					(
-						Some(RegisterTypeEnum::Int(Some(8))),
-						Some(RegisterTypeEnum::Int(Some(8))),
-					) => {
+					(Some(lhs), Some(rhs)) if lhs == cell && rhs == cell => {
 						registers.insert(output_reg.index(), RegisterTypeEnum::Bool);
 					}
-					(Some(RegisterTypeEnum::Int(Some(8))), found) => {
+					(Some(lhs), found) if lhs == cell => {
 						return Err(InstructionsOptimizerError::RegisterInvalid {
 							register: rhs_reg.index(),
-							expected: RegisterTypeEnum::Int(Some(8)),
+							expected: cell,
 							found,
 						});
 					}
 					(found, ..) => {
 						return Err(InstructionsOptimizerError::RegisterInvalid {
 							register: lhs_reg.index(),
-							expected: RegisterTypeEnum::Int(Some(8)),
+							expected: cell,
 							found,
 						});
 					}
@@ -255,13 +304,13 @@ impl Verifier {
 					output_reg,
 				} => {
 					match lhs {
-						RegOrImm::Imm(imm) => assert_eq!(imm.size(), 8),
+						RegOrImm::Imm(imm) => assert_eq!(imm.size(), cell_bits),
 						RegOrImm::Reg(lhs_reg) => match registers.get(&lhs_reg.index()).copied() {
-							Some(RegisterTypeEnum::Int(Some(8))) => {}
+							Some(value) if value == cell => {}
 							found => {
 								return Err(InstructionsOptimizerError::RegisterInvalid {
 									register: lhs_reg.index(),
-									expected: RegisterTypeEnum::Int(Some(8)),
+									expected: cell,
 									found,
 								});
 							}
@@ -269,13 +318,13 @@ impl Verifier {
 					}
 
 					match rhs {
-						RegOrImm::Imm(imm) => assert_eq!(imm.size(), 8),
+						RegOrImm::Imm(imm) => assert_eq!(imm.size(), cell_bits),
 						RegOrImm::Reg(rhs_reg) => match registers.get(&rhs_reg.index()).copied() {
-							Some(RegisterTypeEnum::Int(Some(8))) => {}
+							Some(value) if value == cell => {}
 							found => {
 								return Err(InstructionsOptimizerError::RegisterInvalid {
 									register: rhs_reg.index(),
-									expected: RegisterTypeEnum::Int(Some(8)),
+									expected: cell,
 									found,
 								});
 							}
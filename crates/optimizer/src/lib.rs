@@ -1,59 +1,104 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod diagnostic;
 mod error;
 mod instrs;
 mod ops;
 
-use std::{mem, path::Path};
+use std::mem;
 
-use frick_instructions::{BrainInstruction, ToInstructions};
+use frick_instructions::{BrainInstruction, ToInstructions as _};
 use frick_operations::BrainOperation;
+use frick_serialize::{Artifact, EmitOptions};
+use frick_spec::Spec;
 
-pub use self::{error::OptimizerError, instrs::InstructionsOptimizerError};
+pub use self::{diagnostic::Diagnostic, error::OptimizerError, instrs::InstructionsOptimizerError};
 use self::{instrs::InstructionsOptimizer, ops::OperationsOptimizer};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Optimizer;
 
 impl Optimizer {
+	/// Anything found out about the program while optimizing it is pushed onto `diagnostics`.
 	pub fn run(
 		ops: impl IntoIterator<Item = BrainOperation>,
-		output_path: &Path,
+		spec: Spec,
+		emit: EmitOptions<'_>,
+		diagnostics: &mut Vec<Diagnostic>,
 	) -> Result<Vec<BrainInstruction>, OptimizerError> {
 		let mut ops_optimizer = OperationsOptimizer::new(ops);
 
-		frick_serialize::serialize(&ops_optimizer, output_path, "unoptimized.ops")?;
+		frick_serialize::serialize(
+			&ops_optimizer,
+			emit,
+			Artifact::Operations,
+			"unoptimized.ops",
+		)?;
 
-		{
-			let raw_instrs_optimizer = InstructionsOptimizer::new(
-				ops_optimizer
-					.ops()
-					.iter()
-					.flat_map(ToInstructions::to_instructions),
-			);
+		if emit.artifacts().contains(Artifact::Instructions) {
+			let raw_instrs_optimizer =
+				InstructionsOptimizer::new(Self::lower_operations(ops_optimizer.ops(), spec));
 
 			frick_serialize::serialize(
 				&raw_instrs_optimizer,
-				output_path,
+				emit,
+				Artifact::Instructions,
 				"unoptimized.ops.instrs",
 			)?;
 		}
 
-		ops_optimizer.run();
+		diagnostics.extend(ops_optimizer.run(spec));
 
-		frick_serialize::serialize(&ops_optimizer, output_path, "optimized.ops")?;
+		frick_serialize::serialize(&ops_optimizer, emit, Artifact::Operations, "optimized.ops")?;
 
 		let finished_ops = mem::take(ops_optimizer.ops_mut());
 
-		let mut instrs_optimizer = InstructionsOptimizer::new(
-			finished_ops.into_iter().flat_map(|op| op.to_instructions()),
-		);
+		let mut instrs_optimizer =
+			InstructionsOptimizer::new(Self::lower_operations(&finished_ops, spec));
 
-		frick_serialize::serialize(&instrs_optimizer, output_path, "unoptimized.instrs")?;
+		frick_serialize::serialize(
+			&instrs_optimizer,
+			emit,
+			Artifact::Instructions,
+			"unoptimized.instrs",
+		)?;
 
-		instrs_optimizer.run()?;
+		instrs_optimizer.run(spec)?;
 
-		frick_serialize::serialize(&instrs_optimizer, output_path, "optimized.instrs")?;
+		frick_serialize::serialize(
+			&instrs_optimizer,
+			emit,
+			Artifact::Instructions,
+			"optimized.instrs",
+		)?;
+
+		Ok(mem::take(instrs_optimizer.instrs_mut()))
+	}
+
+	pub fn optimize_operations(
+		ops: impl IntoIterator<Item = BrainOperation>,
+		spec: Spec,
+		diagnostics: &mut Vec<Diagnostic>,
+	) -> Vec<BrainOperation> {
+		let mut ops_optimizer = OperationsOptimizer::new(ops);
+
+		diagnostics.extend(ops_optimizer.run(spec));
+
+		mem::take(ops_optimizer.ops_mut())
+	}
+
+	#[must_use]
+	pub fn lower_operations(ops: &[BrainOperation], spec: Spec) -> Vec<BrainInstruction> {
+		ops.iter().flat_map(|op| op.to_instructions(spec)).collect()
+	}
+
+	pub fn optimize_instructions(
+		instrs: impl IntoIterator<Item = BrainInstruction>,
+		spec: Spec,
+	) -> Result<Vec<BrainInstruction>, InstructionsOptimizerError> {
+		let mut instrs_optimizer = InstructionsOptimizer::new(instrs);
+
+		instrs_optimizer.run(spec)?;
 
 		Ok(mem::take(instrs_optimizer.instrs_mut()))
 	}
@@ -2,7 +2,7 @@ mod change;
 pub mod passes;
 mod utils;
 
-use std::array;
+use std::{array, ops::Range};
 
 use frick_operations::BrainOperation;
 
@@ -19,6 +19,25 @@ pub fn run_loop_pass(v: &mut Vec<BrainOperation>, pass: impl LoopPass) -> bool {
 	})
 }
 
+/// Finds the spans of every loop `pass` would change, without changing any of them.
+pub fn find_loops(v: &[BrainOperation], pass: impl LoopPass) -> Vec<Range<usize>> {
+	let mut spans = Vec::new();
+
+	for op in v {
+		let Some(child_ops) = op.child_ops() else {
+			continue;
+		};
+
+		if pass(child_ops).is_some() {
+			spans.push(op.span());
+		}
+
+		spans.extend(find_loops(child_ops, pass));
+	}
+
+	spans
+}
+
 #[tracing::instrument(skip_all)]
 pub fn run_peephole_pass<const N: usize>(
 	v: &mut Vec<BrainOperation>,
@@ -1,11 +1,18 @@
 use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::CellOverflow;
 use frick_utils::RuntimeArray;
 
 use crate::ops::inner::Change;
 
-pub const fn optimize_clear_cell(ops: &[BrainOperation]) -> Option<Change> {
+pub const fn optimize_clear_cell(ops: &[BrainOperation], overflow: CellOverflow) -> Option<Change> {
 	match ops {
 		[op] => match op.op() {
+			&BrainOperationType::DecrementCell(CellOffsetOptions {
+				value: 1,
+				offset: 0,
+			}) => Some(Change::replace(BrainOperationType::clear_cell())),
+			// any other odd step only reaches zero by wrapping around
+			_ if overflow.is_trapping() => None,
 			&BrainOperationType::DecrementCell(CellOffsetOptions { value, offset: 0 })
 			| &BrainOperationType::IncrementCell(CellOffsetOptions { value, offset: 0 })
 				if !matches!(value % 2, 0) =>
@@ -59,11 +66,18 @@ pub fn remove_infinite_loops(ops: &[BrainOperation]) -> Option<Change> {
 		[
 			..,
 			BrainOperationType::SetCell(CellOffsetOptions {
-				value: 1..=u8::MAX,
+				value: 1..,
 				offset: 0,
 			}),
-		]
-		| [BrainOperationType::InputIntoCell] => Some(Change::remove()),
+		] => Some(Change::remove()),
+		_ => None,
+	}
+}
+
+// Only valid when EOF never stores zero, otherwise this loop reads until the end of input
+pub fn remove_infinite_input_loops(ops: &[BrainOperation]) -> Option<Change> {
+	match ops {
+		[op] if matches!(op.op(), BrainOperationType::InputIntoCell) => Some(Change::remove()),
 		_ => None,
 	}
 }
@@ -2,6 +2,7 @@ mod loops;
 mod peephole;
 
 use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::{CellOverflow, CellWidth};
 
 pub use self::{loops::*, peephole::*};
 
@@ -23,15 +24,21 @@ pub fn fix_beginning_instructions(ops: &mut Vec<BrainOperation>) -> bool {
 	changed_any
 }
 
-pub fn optimize_beginning_incs(ops: &mut Vec<BrainOperation>) -> bool {
+// initial tape contents come in as a prologue of operations at the start of `ops`, so a cell is
+// only known to be zero until an earlier operation in `ops` touches it
+pub fn optimize_beginning_incs(
+	ops: &mut Vec<BrainOperation>,
+	width: CellWidth,
+	overflow: CellOverflow,
+) -> bool {
 	let mut changed_any = false;
 
 	let mut i = 0;
 
 	let mut indices_checked = Vec::new();
 
-	loop {
-		match *ops[i].op() {
+	while let Some(op) = ops.get(i) {
+		match *op.op() {
 			BrainOperationType::IncrementCell(CellOffsetOptions { value, offset }) => {
 				if indices_checked.contains(&offset) {
 					break;
@@ -46,7 +53,12 @@ pub fn optimize_beginning_incs(ops: &mut Vec<BrainOperation>) -> bool {
 					break;
 				}
 
-				*ops[i].op_mut() = BrainOperationType::set_cell_at(0u8.wrapping_sub(value), offset);
+				// decrementing a zeroed cell is exactly what should trap
+				let Some(value) = overflow.sub(width, 0, value) else {
+					break;
+				};
+
+				*ops[i].op_mut() = BrainOperationType::set_cell_at(value, offset);
 				changed_any = true;
 				indices_checked.push(offset);
 			}
@@ -59,11 +71,15 @@ pub fn optimize_beginning_incs(ops: &mut Vec<BrainOperation>) -> bool {
 					*i = i.wrapping_sub(offset);
 				}
 			}
-			BrainOperationType::SetCell(CellOffsetOptions { value: 0, .. }) => {
+			BrainOperationType::SetCell(CellOffsetOptions { value: 0, offset })
+				if !indices_checked.contains(&offset) =>
+			{
 				ops.remove(i);
 
 				return true;
 			}
+			// clears a cell the prologue or an earlier folded increment set
+			BrainOperationType::SetCell(CellOffsetOptions { value: 0, .. }) => {}
 			BrainOperationType::SetCell(CellOffsetOptions { offset, .. }) => {
 				if indices_checked.contains(&offset) {
 					break;
@@ -96,11 +112,7 @@ pub fn optimize_beginning_incs(ops: &mut Vec<BrainOperation>) -> bool {
 pub fn remove_non_io_ending_operations(ops: &mut Vec<BrainOperation>) -> bool {
 	let old_len = ops.len();
 
-	loop {
-		if ops.last().is_some_and(|o| o.has_io()) {
-			break;
-		}
-
+	while ops.last().is_some_and(|o| !o.has_io()) {
 		ops.pop();
 	}
 
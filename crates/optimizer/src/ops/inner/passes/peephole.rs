@@ -1,7 +1,19 @@
 use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::{CellOverflow, CellWidth, TapeBoundary};
 
 use crate::ops::inner::{Change, utils::is_basic_inc_dec_loop};
 
+// wider cells would otherwise let a single loop unroll billions of times
+const MAX_UNROLLED_ITERATIONS: u32 = u8::MAX as u32;
+
+// folding moves skips the check on wherever the pointer was in between, which is only safe when
+// that position lies between cells the folded ops still check
+fn stays_in_bounds(boundary: TapeBoundary, position: i32, checked: [i32; 2]) -> bool {
+	let [a, b] = checked;
+
+	!boundary.is_trapping() || (a.min(b).min(0)..=a.max(b).max(0)).contains(&position)
+}
+
 pub fn remove_noop_ops(ops: [&BrainOperation; 1]) -> Option<Change> {
 	match ops.map(BrainOperation::op) {
 		[
@@ -15,7 +27,12 @@ pub fn remove_noop_ops(ops: [&BrainOperation; 1]) -> Option<Change> {
 	}
 }
 
-pub fn optimize_consecutive_ops(ops: [&BrainOperation; 2]) -> Option<Change> {
+pub fn optimize_consecutive_ops(
+	ops: [&BrainOperation; 2],
+	width: CellWidth,
+	overflow: CellOverflow,
+	boundary: TapeBoundary,
+) -> Option<Change> {
 	match ops.map(BrainOperation::op) {
 		[
 			&BrainOperationType::IncrementCell(CellOffsetOptions {
@@ -27,7 +44,7 @@ pub fn optimize_consecutive_ops(ops: [&BrainOperation; 2]) -> Option<Change> {
 				offset: y,
 			}),
 		] if x == y => Some(Change::replace(BrainOperationType::increment_cell_at(
-			a.wrapping_add(b),
+			overflow.add(width, a, b)?,
 			x,
 		))),
 		[
@@ -40,17 +57,18 @@ pub fn optimize_consecutive_ops(ops: [&BrainOperation; 2]) -> Option<Change> {
 				offset: y,
 			}),
 		] if x == y => Some(Change::replace(BrainOperationType::decrement_cell_at(
-			a.wrapping_add(b),
+			overflow.add(width, a, b)?,
 			x,
 		))),
 		[
 			&BrainOperationType::MovePointer(a),
 			&BrainOperationType::MovePointer(b),
-		] => Some(if a == -b {
+		] if stays_in_bounds(boundary, a, [a.wrapping_add(b), 0]) => Some(if a == -b {
 			Change::remove()
 		} else {
 			Change::replace(BrainOperationType::MovePointer(a.wrapping_add(b)))
 		}),
+		// cancelling these out would skip a trap in between
 		[
 			&BrainOperationType::IncrementCell(CellOffsetOptions {
 				value: a,
@@ -70,20 +88,24 @@ pub fn optimize_consecutive_ops(ops: [&BrainOperation; 2]) -> Option<Change> {
 				value: a,
 				offset: x,
 			}),
-		] if x == y => Some(if a == b {
+		] if x == y && !overflow.is_trapping() => Some(if a == b {
 			Change::remove()
 		} else {
 			Change::replace(if a > b {
-				BrainOperationType::increment_cell_at(a.wrapping_sub(b), x)
+				BrainOperationType::increment_cell_at(a - b, x)
 			} else {
-				BrainOperationType::decrement_cell_at(b.wrapping_sub(a), x)
+				BrainOperationType::decrement_cell_at(b - a, x)
 			})
 		}),
 		_ => None,
 	}
 }
 
-pub fn optimize_set_cell(ops: [&BrainOperation; 2]) -> Option<Change> {
+pub fn optimize_set_cell(
+	ops: [&BrainOperation; 2],
+	width: CellWidth,
+	overflow: CellOverflow,
+) -> Option<Change> {
 	match ops.map(BrainOperation::op) {
 		[
 			i,
@@ -97,6 +119,10 @@ pub fn optimize_set_cell(ops: [&BrainOperation; 2]) -> Option<Change> {
 			| &BrainOperationType::DecrementCell(CellOffsetOptions { offset: 0, .. })
 			| &BrainOperationType::SetCell(CellOffsetOptions { offset: 0, .. }),
 			&BrainOperationType::SetCell(CellOffsetOptions { offset: 0, .. }),
+		] if !overflow.is_trapping() => Some(Change::remove_offset(0)),
+		[
+			&BrainOperationType::SetCell(CellOffsetOptions { offset: 0, .. }),
+			&BrainOperationType::SetCell(CellOffsetOptions { offset: 0, .. }),
 		] => Some(Change::remove_offset(0)),
 		[
 			i,
@@ -110,7 +136,7 @@ pub fn optimize_set_cell(ops: [&BrainOperation; 2]) -> Option<Change> {
 			&BrainOperationType::IncrementCell(inc_options),
 		] if set_options.offset() == inc_options.offset() => {
 			Some(Change::replace(BrainOperationType::set_cell_at(
-				set_options.value().wrapping_add(inc_options.value()),
+				overflow.add(width, set_options.value(), inc_options.value())?,
 				set_options.offset(),
 			)))
 		}
@@ -119,7 +145,7 @@ pub fn optimize_set_cell(ops: [&BrainOperation; 2]) -> Option<Change> {
 			&BrainOperationType::DecrementCell(dec_options),
 		] if set_options.offset() == dec_options.offset() => {
 			Some(Change::replace(BrainOperationType::set_cell_at(
-				set_options.value().wrapping_sub(dec_options.value()),
+				overflow.sub(width, set_options.value(), dec_options.value())?,
 				set_options.offset(),
 			)))
 		}
@@ -157,14 +183,14 @@ pub fn optimize_output_value(ops: [&BrainOperation; 2]) -> Option<Change> {
 				offset: 0,
 			}),
 		] => Some(Change::swap([
-			BrainOperation::new(BrainOperationType::OutputValue(value), ops[1].span()),
+			BrainOperation::new(BrainOperationType::OutputValue(value as u8), ops[1].span()),
 			BrainOperation::new(BrainOperationType::set_cell_at(value, 0), ops[0].span()),
 		])),
 		_ => None,
 	}
 }
 
-pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
+pub fn optimize_output_cell(ops: [&BrainOperation; 3], width: CellWidth) -> Option<Change> {
 	match ops.map(BrainOperation::op) {
 		[
 			&BrainOperationType::IncrementCell(CellOffsetOptions {
@@ -182,23 +208,23 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => {
 			if inc_value == dec_value {
 				return Some(Change::replace(BrainOperationType::OutputCell(
-					CellOffsetOptions::new(inc_value.wrapping_add(output_value), 0),
+					CellOffsetOptions::new(width.wrapping_add(inc_value, output_value), 0),
 				)));
 			}
 
 			Some(Change::swap([
 				BrainOperation::new(
 					BrainOperationType::OutputCell(CellOffsetOptions::new(
-						inc_value.wrapping_add(output_value),
+						width.wrapping_add(inc_value, output_value),
 						0,
 					)),
 					ops[0].span().start..ops[1].span().end,
 				),
 				BrainOperation::new(
 					if inc_value > dec_value {
-						BrainOperationType::increment_cell(inc_value.wrapping_sub(dec_value))
+						BrainOperationType::increment_cell(inc_value - dec_value)
 					} else {
-						BrainOperationType::decrement_cell(dec_value.wrapping_sub(inc_value))
+						BrainOperationType::decrement_cell(dec_value - inc_value)
 					},
 					ops[2].span(),
 				),
@@ -220,23 +246,23 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => {
 			if dec_value == inc_value {
 				return Some(Change::replace(BrainOperationType::OutputCell(
-					CellOffsetOptions::new(output_value.wrapping_sub(dec_value), 0),
+					CellOffsetOptions::new(width.wrapping_sub(output_value, dec_value), 0),
 				)));
 			}
 
 			Some(Change::swap([
 				BrainOperation::new(
 					BrainOperationType::OutputCell(CellOffsetOptions::new(
-						output_value.wrapping_sub(dec_value),
+						width.wrapping_sub(output_value, dec_value),
 						0,
 					)),
 					ops[0].span().start..ops[1].span().end,
 				),
 				BrainOperation::new(
 					if inc_value > dec_value {
-						BrainOperationType::increment_cell(inc_value.wrapping_sub(dec_value))
+						BrainOperationType::increment_cell(inc_value - dec_value)
 					} else {
-						BrainOperationType::decrement_cell(dec_value.wrapping_sub(inc_value))
+						BrainOperationType::decrement_cell(dec_value - inc_value)
 					},
 					ops[2].span(),
 				),
@@ -258,13 +284,13 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::OutputCell(CellOffsetOptions::new(
-					a.wrapping_add(output_value),
+					width.wrapping_add(a, output_value),
 					0,
 				)),
 				ops[0].span().start..ops[1].span().end,
 			),
 			BrainOperation::new(
-				BrainOperationType::increment_cell(a.wrapping_add(b)),
+				BrainOperationType::increment_cell(width.wrapping_add(a, b)),
 				ops[2].span(),
 			),
 		])),
@@ -284,13 +310,13 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::OutputCell(CellOffsetOptions::new(
-					output_value.wrapping_sub(a),
+					width.wrapping_sub(output_value, a),
 					0,
 				)),
 				ops[0].span().start..ops[1].span().end,
 			),
 			BrainOperation::new(
-				BrainOperationType::decrement_cell(a.wrapping_add(b)),
+				BrainOperationType::decrement_cell(width.wrapping_add(a, b)),
 				ops[2].span(),
 			),
 		])),
@@ -307,7 +333,7 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::OutputCell(CellOffsetOptions::new(
-					output_value.wrapping_add(inc_value),
+					width.wrapping_add(output_value, inc_value),
 					0,
 				)),
 				ops[0].span().start..ops[1].span().end,
@@ -327,7 +353,7 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 		] => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::OutputCell(CellOffsetOptions::new(
-					output_value.wrapping_sub(dec_value),
+					width.wrapping_sub(output_value, dec_value),
 					0,
 				)),
 				ops[0].span().start..ops[1].span().end,
@@ -338,13 +364,17 @@ pub fn optimize_output_cell(ops: [&BrainOperation; 3]) -> Option<Change> {
 	}
 }
 
-pub fn add_offsets(ops: [&BrainOperation; 3]) -> Option<Change> {
+pub fn add_offsets(ops: [&BrainOperation; 3], boundary: TapeBoundary) -> Option<Change> {
+	let folds = |x: i32, offset: i32, y: i32| {
+		stays_in_bounds(boundary, x, [offset.wrapping_add(x), x.wrapping_add(y)])
+	};
+
 	match ops.map(BrainOperation::op) {
 		[
 			&BrainOperationType::MovePointer(x),
 			&BrainOperationType::IncrementCell(options),
 			&BrainOperationType::MovePointer(y),
-		] => Some(Change::swap([
+		] if folds(x, options.offset(), y) => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::increment_cell_at(
 					options.value(),
@@ -361,7 +391,7 @@ pub fn add_offsets(ops: [&BrainOperation; 3]) -> Option<Change> {
 			&BrainOperationType::MovePointer(x),
 			&BrainOperationType::DecrementCell(options),
 			&BrainOperationType::MovePointer(y),
-		] => Some(Change::swap([
+		] if folds(x, options.offset(), y) => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::decrement_cell_at(
 					options.value(),
@@ -378,7 +408,7 @@ pub fn add_offsets(ops: [&BrainOperation; 3]) -> Option<Change> {
 			&BrainOperationType::MovePointer(x),
 			&BrainOperationType::SetCell(options),
 			&BrainOperationType::MovePointer(y),
-		] => Some(Change::swap([
+		] if folds(x, options.offset(), y) => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::set_cell_at(options.value(), options.offset().wrapping_add(x)),
 				ops[0].span().start..ops[1].span().end,
@@ -392,7 +422,7 @@ pub fn add_offsets(ops: [&BrainOperation; 3]) -> Option<Change> {
 			&BrainOperationType::MovePointer(x),
 			&BrainOperationType::OutputCell(options),
 			&BrainOperationType::MovePointer(y),
-		] => Some(Change::swap([
+		] if folds(x, options.offset(), y) => Some(Change::swap([
 			BrainOperation::new(
 				BrainOperationType::OutputCell(CellOffsetOptions::new(
 					options.value(),
@@ -409,13 +439,13 @@ pub fn add_offsets(ops: [&BrainOperation; 3]) -> Option<Change> {
 	}
 }
 
-pub fn optimize_constant_moves(ops: [&BrainOperation; 2]) -> Option<Change> {
+pub fn optimize_constant_moves(ops: [&BrainOperation; 2], width: CellWidth) -> Option<Change> {
 	match ops.map(BrainOperation::op) {
 		[
 			&BrainOperationType::SetCell(set_options),
 			&BrainOperationType::MoveCellValue(move_options),
 		] if matches!(set_options.offset(), 0) => {
-			let value_to_add = set_options.value().wrapping_mul(move_options.value());
+			let value_to_add = width.wrapping_mul(set_options.value(), move_options.value());
 
 			Some(Change::swap([
 				BrainOperation::new(BrainOperationType::clear_cell(), ops[0].span()),
@@ -429,7 +459,7 @@ pub fn optimize_constant_moves(ops: [&BrainOperation; 2]) -> Option<Change> {
 			&BrainOperationType::SetCell(set_options),
 			&BrainOperationType::TakeCellValue(take_options),
 		] if matches!(set_options.offset(), 0) => {
-			let value_to_add = set_options.value().wrapping_mul(take_options.value());
+			let value_to_add = width.wrapping_mul(set_options.value(), take_options.value());
 
 			Some(Change::swap([
 				BrainOperation::new(BrainOperationType::clear_cell(), ops[0].span()),
@@ -494,7 +524,7 @@ pub fn unroll_constant_loop(ops: [&BrainOperation; 2]) -> Option<Change> {
 				offset: 0,
 			}),
 			BrainOperationType::DynamicLoop(loop_ops),
-		] if is_basic_inc_dec_loop(loop_ops) => {
+		] if set_value <= MAX_UNROLLED_ITERATIONS && is_basic_inc_dec_loop(loop_ops) => {
 			let decrement_index = {
 				let mut idx = None;
 
@@ -1,11 +1,15 @@
 mod inner;
 
+use std::ops::Range;
+
 use frick_operations::BrainOperation;
+use frick_spec::{EofMode, Spec};
 use frick_utils::IntoIteratorExt as _;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use self::inner::{passes, run_loop_pass, run_peephole_pass};
+use self::inner::{LoopPass, find_loops, passes, run_loop_pass, run_peephole_pass};
+use crate::Diagnostic;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -22,56 +26,154 @@ impl OperationsOptimizer {
 	}
 
 	#[tracing::instrument("optimize operations", skip(self))]
-	pub fn run(&mut self) {
+	pub fn run(&mut self, spec: Spec) -> Vec<Diagnostic> {
 		let mut iteration = 0;
+		let mut diagnostics = Vec::new();
 
-		let mut progress = self.run_passes(iteration);
+		let mut progress = self.run_passes(iteration, spec, &mut diagnostics);
 
 		while progress {
 			iteration += 1;
-			progress = self.run_passes(iteration);
+			progress = self.run_passes(iteration, spec, &mut diagnostics);
 		}
 
 		info!(iterations = iteration);
+
+		diagnostics
 	}
 
-	#[tracing::instrument(skip(self))]
-	fn run_passes(&mut self, iteration: usize) -> bool {
+	#[tracing::instrument(skip(self, diagnostics))]
+	fn run_passes(
+		&mut self,
+		iteration: usize,
+		spec: Spec,
+		diagnostics: &mut Vec<Diagnostic>,
+	) -> bool {
 		let mut progress = false;
 
-		self.run_each_pass(&mut progress);
+		self.run_each_pass(&mut progress, spec, diagnostics);
 
 		progress
 	}
 
-	fn run_each_pass(&mut self, progress: &mut bool) {
-		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_consecutive_ops);
-		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_set_cell);
-		*progress |= run_loop_pass(self.ops_mut(), passes::optimize_clear_cell);
+	fn run_each_pass(
+		&mut self,
+		progress: &mut bool,
+		spec: Spec,
+		diagnostics: &mut Vec<Diagnostic>,
+	) {
+		let width = spec.cell_width();
+		let overflow = spec.overflow();
+		let boundary = spec.boundary();
+
+		*progress |= run_peephole_pass(self.ops_mut(), move |ops| {
+			passes::optimize_consecutive_ops(ops, width, overflow, boundary)
+		});
+		*progress |= run_peephole_pass(self.ops_mut(), move |ops| {
+			passes::optimize_set_cell(ops, width, overflow)
+		});
+		*progress |= run_loop_pass(self.ops_mut(), move |ops| {
+			passes::optimize_clear_cell(ops, overflow)
+		});
 		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_output_value);
-		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_output_cell);
-		*progress |= run_peephole_pass(self.ops_mut(), passes::add_offsets);
 
-		*progress |= run_loop_pass(self.ops_mut(), passes::optimize_move_cell_value);
-		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_constant_moves);
+		// these fold away increments and decrements that have to be able to trap
+		if !overflow.is_trapping() {
+			*progress |= run_peephole_pass(self.ops_mut(), move |ops| {
+				passes::optimize_output_cell(ops, width)
+			});
+		}
+
+		*progress |= run_peephole_pass(self.ops_mut(), move |ops| {
+			passes::add_offsets(ops, boundary)
+		});
+
+		if !overflow.is_trapping() {
+			*progress |= run_loop_pass(self.ops_mut(), passes::optimize_move_cell_value);
+		}
+
+		*progress |= run_peephole_pass(self.ops_mut(), move |ops| {
+			passes::optimize_constant_moves(ops, width)
+		});
 		*progress |= run_peephole_pass(self.ops_mut(), passes::optimize_take_cell_value);
 
-		*progress |= passes::fix_beginning_instructions(self.ops_mut());
-		*progress |= passes::optimize_beginning_incs(self.ops_mut());
-		*progress |= passes::remove_non_io_ending_operations(self.ops_mut());
-		*progress |= passes::remove_ops_before_output_value(self.ops_mut());
+		// the passes below can drop a loop that never terminates along with everything around it
+		self.report_infinite_loops(spec, diagnostics);
+
+		// a caller owned tape may not start out zeroed
+		if !spec.strict_semantics() {
+			*progress |= passes::fix_beginning_instructions(self.ops_mut());
+			*progress |= passes::optimize_beginning_incs(self.ops_mut(), width, overflow);
+		}
+
+		// trailing work can still trap, never terminate, or show up in the final tape
+		if !overflow.is_trapping() && !spec.strict_semantics() {
+			*progress |= passes::remove_non_io_ending_operations(self.ops_mut());
+			*progress |= passes::remove_ops_before_output_value(self.ops_mut());
+		}
 
 		*progress |= run_peephole_pass(self.ops_mut(), passes::remove_unreachable_loops);
-		*progress |= run_peephole_pass(self.ops_mut(), passes::remove_changes_before_input);
+
+		if !matches!(spec.eof(), EofMode::Unchanged) && !overflow.is_trapping() {
+			*progress |= run_peephole_pass(self.ops_mut(), passes::remove_changes_before_input);
+		}
+
 		*progress |= run_peephole_pass(self.ops_mut(), passes::remove_noop_ops);
 		*progress |= run_peephole_pass(self.ops_mut(), passes::remove_redundant_offsets);
-		*progress |= run_loop_pass(self.ops_mut(), passes::remove_infinite_loops);
+		self.report_infinite_loops(spec, diagnostics);
+
+		// removing a loop that never terminates turns a hang into the program carrying on
+		if !spec.strict_semantics() {
+			*progress |= run_loop_pass(self.ops_mut(), passes::remove_infinite_loops);
+
+			if !matches!(spec.eof(), EofMode::Zero) {
+				*progress |= run_loop_pass(self.ops_mut(), passes::remove_infinite_input_loops);
+			}
+		}
 
 		*progress |= run_peephole_pass(self.ops_mut(), passes::unroll_constant_loop);
 
 		*progress |= run_loop_pass(self.ops_mut(), passes::optimize_clear_decrement_loop);
 	}
 
+	fn report_infinite_loops(&self, spec: Spec, diagnostics: &mut Vec<Diagnostic>) {
+		self.report_loops(
+			passes::remove_infinite_loops,
+			Diagnostic::InfiniteLoop,
+			diagnostics,
+		);
+
+		if !matches!(spec.eof(), EofMode::Zero) {
+			self.report_loops(
+				passes::remove_infinite_input_loops,
+				Diagnostic::InfiniteInputLoop,
+				diagnostics,
+			);
+		}
+	}
+
+	/// Reports every loop `pass` would change, before it gets the chance to.
+	fn report_loops(
+		&self,
+		pass: impl LoopPass,
+		diagnostic: fn(Range<usize>) -> Diagnostic,
+		diagnostics: &mut Vec<Diagnostic>,
+	) {
+		// strict semantics keeps these loops around, so only report each one the first time
+		for span in find_loops(self.ops(), pass) {
+			// the initial tape's prologue has no place in the source to point at
+			if span.is_empty() {
+				continue;
+			}
+
+			let diagnostic = diagnostic(span);
+
+			if !diagnostics.contains(&diagnostic) {
+				diagnostics.push(diagnostic);
+			}
+		}
+	}
+
 	pub const fn ops(&self) -> &Vec<BrainOperation> {
 		&self.ops
 	}
@@ -80,3 +182,96 @@ impl OperationsOptimizer {
 		&mut self.ops
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions, InitialTape};
+	use frick_spec::{Spec, TapeBoundary};
+
+	use super::OperationsOptimizer;
+
+	fn optimize(program: &str, spec: Spec) -> Vec<BrainOperation> {
+		let mut optimizer = OperationsOptimizer::new(frick_operations::parse_str(program).unwrap());
+
+		optimizer.run(spec);
+
+		optimizer.ops
+	}
+
+	#[test]
+	fn strict_semantics_keeps_work_on_the_starting_tape() {
+		let ops = optimize("[->+<]", Spec::new().with_strict_semantics(true));
+
+		assert!(!ops.is_empty());
+		assert!(
+			!ops.iter()
+				.any(|op| matches!(op.op(), BrainOperationType::SetCell(..)))
+		);
+	}
+
+	#[test]
+	fn strict_semantics_keeps_increments() {
+		let ops = optimize("+++>++", Spec::new().with_strict_semantics(true));
+
+		assert!(
+			ops.iter()
+				.all(|op| !matches!(op.op(), BrainOperationType::SetCell(..)))
+		);
+		assert!(!ops.is_empty());
+	}
+
+	/// The value the last [`BrainOperationType::SetCell`] before the pointer first moves leaves in
+	/// the cell at `offset`.
+	fn value_before_moving(ops: &[BrainOperation], offset: i32) -> Option<u32> {
+		ops.iter()
+			.take_while(|op| !matches!(op.op(), BrainOperationType::MovePointer(..)))
+			.filter_map(|op| match *op.op() {
+				BrainOperationType::SetCell(CellOffsetOptions { value, offset: o })
+					if o == offset =>
+				{
+					Some(value)
+				}
+				_ => None,
+			})
+			.last()
+	}
+
+	#[test]
+	fn clears_cells_from_the_initial_tape() {
+		let mut ops = InitialTape::new().with_cells([0, 7]).prologue();
+		ops.extend(frick_operations::parse_str(">[-]<+>.").unwrap());
+
+		let mut optimizer = OperationsOptimizer::new(ops);
+		optimizer.run(Spec::new());
+
+		assert_eq!(value_before_moving(&optimizer.ops, 1), Some(0));
+		assert_eq!(value_before_moving(&optimizer.ops, 0), Some(1));
+	}
+
+	#[test]
+	fn keeps_moves_that_leave_a_bounded_tape() {
+		for boundary in [TapeBoundary::Abort, TapeBoundary::Grow] {
+			let spec = Spec::new()
+				.with_boundary(boundary)
+				.with_strict_semantics(true);
+
+			assert!(!optimize("<>", spec).is_empty(), "{boundary:?}");
+			assert!(!optimize("<+>>-<", spec).is_empty(), "{boundary:?}");
+		}
+
+		let spec = Spec::new().with_strict_semantics(true);
+
+		assert!(optimize("<>", spec).is_empty());
+	}
+
+	#[test]
+	fn only_increments() {
+		assert!(optimize("+++", Spec::new()).is_empty());
+	}
+
+	#[test]
+	fn empty_program() {
+		assert!(optimize("", Spec::new()).is_empty());
+		assert!(optimize("", Spec::new().with_strict_semantics(true)).is_empty());
+	}
+}
@@ -0,0 +1,140 @@
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Artifact {
+	Operations,
+	Instructions,
+	LlvmIr,
+	Bitcode,
+	Object,
+	Assembly,
+	CSource,
+	RustSource,
+}
+
+impl Artifact {
+	const fn bit(self) -> u8 {
+		1 << self as u8
+	}
+
+	#[must_use]
+	pub const fn extension(self) -> &'static str {
+		match self {
+			Self::Operations => "ops",
+			Self::Instructions => "instrs",
+			Self::LlvmIr => "ll",
+			Self::Bitcode => "bc",
+			Self::Object => "o",
+			Self::Assembly => "s",
+			Self::CSource => "c",
+			Self::RustSource => "rs",
+		}
+	}
+}
+
+impl Display for Artifact {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match *self {
+			Self::Operations => "operations",
+			Self::Instructions => "instructions",
+			Self::LlvmIr => "LLVM IR",
+			Self::Bitcode => "LLVM bitcode",
+			Self::Object => "object file",
+			Self::Assembly => "assembly",
+			Self::CSource => "C source",
+			Self::RustSource => "Rust source",
+		})
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Artifacts(u8);
+
+impl Artifacts {
+	#[must_use]
+	pub const fn empty() -> Self {
+		Self(0)
+	}
+
+	#[must_use]
+	pub const fn all() -> Self {
+		Self::empty()
+			.with(Artifact::Operations)
+			.with(Artifact::Instructions)
+			.with(Artifact::LlvmIr)
+			.with(Artifact::Bitcode)
+			.with(Artifact::Object)
+			.with(Artifact::Assembly)
+			.with(Artifact::CSource)
+			.with(Artifact::RustSource)
+	}
+
+	#[must_use]
+	pub const fn with(self, artifact: Artifact) -> Self {
+		Self(self.0 | artifact.bit())
+	}
+
+	#[must_use]
+	pub const fn contains(self, artifact: Artifact) -> bool {
+		!matches!(self.0 & artifact.bit(), 0)
+	}
+
+	#[must_use]
+	pub const fn is_empty(self) -> bool {
+		matches!(self.0, 0)
+	}
+}
+
+impl From<Artifact> for Artifacts {
+	fn from(value: Artifact) -> Self {
+		Self::empty().with(value)
+	}
+}
+
+impl FromIterator<Artifact> for Artifacts {
+	fn from_iter<T: IntoIterator<Item = Artifact>>(iter: T) -> Self {
+		iter.into_iter().fold(Self::empty(), Self::with)
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EmitOptions<'a> {
+	folder_path: Option<&'a Path>,
+	artifacts: Artifacts,
+}
+
+impl<'a> EmitOptions<'a> {
+	#[must_use]
+	pub const fn new(folder_path: &'a Path, artifacts: Artifacts) -> Self {
+		Self {
+			folder_path: Some(folder_path),
+			artifacts,
+		}
+	}
+
+	#[must_use]
+	pub const fn none() -> Self {
+		Self {
+			folder_path: None,
+			artifacts: Artifacts::empty(),
+		}
+	}
+
+	#[must_use]
+	pub const fn artifacts(self) -> Artifacts {
+		self.artifacts
+	}
+
+	#[must_use]
+	pub const fn folder_for(self, artifact: Artifact) -> Option<&'a Path> {
+		if self.artifacts.contains(artifact) {
+			self.folder_path
+		} else {
+			None
+		}
+	}
+}
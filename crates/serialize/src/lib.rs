@@ -1,5 +1,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod artifact;
+
 use std::{
 	error::Error,
 	fmt::{Display, Formatter, Result as FmtResult},
@@ -10,6 +12,8 @@ use std::{
 
 use serde::Serialize;
 
+pub use self::artifact::*;
+
 #[derive(Debug)]
 pub enum SerializeError {
 	#[cfg(feature = "ron")]
@@ -52,9 +56,14 @@ impl From<ron::Error> for SerializeError {
 
 pub fn serialize<T: Serialize>(
 	value: &T,
-	folder_path: &Path,
+	emit: EmitOptions<'_>,
+	artifact: Artifact,
 	file_name: &str,
 ) -> Result<(), SerializeError> {
+	let Some(folder_path) = emit.folder_for(artifact) else {
+		return Ok(());
+	};
+
 	#[cfg(feature = "ron")]
 	serialize_as_ron(value, folder_path, file_name)?;
 
@@ -82,3 +91,81 @@ fn serialize_as_ron<T: Serialize>(
 
 	Ok(())
 }
+
+#[cfg(all(test, feature = "ron"))]
+mod tests {
+	use std::{env, fs, path::PathBuf, process};
+
+	use super::{Artifact, Artifacts, EmitOptions, serialize};
+
+	const ARTIFACTS: [Artifact; 8] = [
+		Artifact::Operations,
+		Artifact::Instructions,
+		Artifact::LlvmIr,
+		Artifact::Bitcode,
+		Artifact::Object,
+		Artifact::Assembly,
+		Artifact::CSource,
+		Artifact::RustSource,
+	];
+
+	fn output_dir(artifact: Artifact) -> PathBuf {
+		let dir = env::temp_dir().join(format!(
+			"frick-serialize-{}-{}",
+			process::id(),
+			artifact.extension()
+		));
+
+		_ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+
+		dir
+	}
+
+	#[test]
+	fn only_selected_artifact_is_written() {
+		for selected in ARTIFACTS {
+			let dir = output_dir(selected);
+			let emit = EmitOptions::new(&dir, Artifacts::from(selected));
+
+			for artifact in ARTIFACTS {
+				serialize(&[1u8, 2, 3], emit, artifact, artifact.extension()).unwrap();
+			}
+
+			let written = fs::read_dir(&dir)
+				.unwrap()
+				.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+				.collect::<Vec<_>>();
+
+			assert_eq!(written, [format!("{}.ron", selected.extension())]);
+
+			fs::remove_dir_all(&dir).unwrap();
+		}
+	}
+
+	#[test]
+	fn nothing_is_written_by_default() {
+		let emit = EmitOptions::none();
+
+		for artifact in ARTIFACTS {
+			assert!(emit.folder_for(artifact).is_none());
+			serialize(&(), emit, artifact, "unused").unwrap();
+		}
+	}
+
+	#[test]
+	fn artifacts_only_contain_what_was_added() {
+		let artifacts = [Artifact::Operations, Artifact::Object]
+			.into_iter()
+			.collect::<Artifacts>();
+
+		for artifact in ARTIFACTS {
+			assert_eq!(
+				artifacts.contains(artifact),
+				matches!(artifact, Artifact::Operations | Artifact::Object)
+			);
+			assert!(Artifacts::all().contains(artifact));
+			assert!(!Artifacts::empty().contains(artifact));
+		}
+	}
+}
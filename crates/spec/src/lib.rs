@@ -1,7 +1,319 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![no_std]
 
+use core::time::Duration;
+
 pub const POINTER_SIZE: usize = core::mem::size_of::<usize>() * 8;
 pub const TAPE_SIZE: usize = 0x8000;
+pub const OUTPUT_BUFFER_SIZE: usize = 0x2000;
 
 const _: () = const { assert!(TAPE_SIZE.is_power_of_two()) };
+
+/// What happens when the tape pointer moves past either end of the tape.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TapeBoundary {
+	/// The pointer wraps around to the other end of the tape.
+	#[default]
+	Wrap,
+	/// The program exits with an error pointing at the offending instruction.
+	Abort,
+	/// The tape is reallocated on the heap when moving past the right end, moving past the left
+	/// end still aborts.
+	Grow,
+}
+
+impl TapeBoundary {
+	/// Whether moving the pointer can stop the program, [`TapeBoundary::Grow`] still does so past
+	/// the left end of the tape.
+	#[must_use]
+	pub const fn is_trapping(self) -> bool {
+		!matches!(self, Self::Wrap)
+	}
+}
+
+/// How many bits each cell on the tape holds, cell arithmetic wraps around at this width.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CellWidth {
+	#[default]
+	U8,
+	U16,
+	U32,
+}
+
+impl CellWidth {
+	#[must_use]
+	pub const fn bits(self) -> u32 {
+		match self {
+			Self::U8 => 8,
+			Self::U16 => 16,
+			Self::U32 => 32,
+		}
+	}
+
+	#[must_use]
+	pub const fn bytes(self) -> usize {
+		self.bits() as usize / 8
+	}
+
+	#[must_use]
+	pub const fn max(self) -> u32 {
+		u32::MAX >> (32 - self.bits())
+	}
+
+	/// Truncates `value` to the bits a cell can hold.
+	#[must_use]
+	pub const fn wrap(self, value: u32) -> u32 {
+		value & self.max()
+	}
+
+	#[must_use]
+	pub const fn wrapping_add(self, lhs: u32, rhs: u32) -> u32 {
+		self.wrap(lhs.wrapping_add(rhs))
+	}
+
+	#[must_use]
+	pub const fn wrapping_sub(self, lhs: u32, rhs: u32) -> u32 {
+		self.wrap(lhs.wrapping_sub(rhs))
+	}
+
+	#[must_use]
+	pub const fn wrapping_mul(self, lhs: u32, rhs: u32) -> u32 {
+		self.wrap(lhs.wrapping_mul(rhs))
+	}
+
+	/// Returns [`None`] if the sum doesn't fit in a cell.
+	#[must_use]
+	pub const fn checked_add(self, lhs: u32, rhs: u32) -> Option<u32> {
+		match lhs.checked_add(rhs) {
+			Some(value) if value <= self.max() => Some(value),
+			_ => None,
+		}
+	}
+
+	/// Returns [`None`] if the difference would go below zero.
+	// every width bottoms out at zero, this only mirrors `checked_add`
+	#[allow(clippy::unused_self)]
+	#[must_use]
+	pub const fn checked_sub(self, lhs: u32, rhs: u32) -> Option<u32> {
+		lhs.checked_sub(rhs)
+	}
+}
+
+/// What happens when cell arithmetic goes past either end of what a cell can hold.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CellOverflow {
+	/// The value wraps around to the other end of the cell's range.
+	#[default]
+	Wrap,
+	/// The program exits with an error pointing at the offending instruction.
+	Trap,
+}
+
+impl CellOverflow {
+	/// Folds two constant increments together, returns [`None`] if the result would trap.
+	#[must_use]
+	pub const fn add(self, width: CellWidth, lhs: u32, rhs: u32) -> Option<u32> {
+		match self {
+			Self::Wrap => Some(width.wrapping_add(lhs, rhs)),
+			Self::Trap => width.checked_add(lhs, rhs),
+		}
+	}
+
+	/// Folds a constant decrement into a value, returns [`None`] if the result would trap.
+	#[must_use]
+	pub const fn sub(self, width: CellWidth, lhs: u32, rhs: u32) -> Option<u32> {
+		match self {
+			Self::Wrap => Some(width.wrapping_sub(lhs, rhs)),
+			Self::Trap => width.checked_sub(lhs, rhs),
+		}
+	}
+
+	#[must_use]
+	pub const fn is_trapping(self) -> bool {
+		matches!(self, Self::Trap)
+	}
+}
+
+/// The value an input instruction leaves in the cell once input has run out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EofMode {
+	Zero,
+	Max,
+	#[default]
+	Newline,
+	/// The cell keeps whatever value it had before the input instruction.
+	Unchanged,
+}
+
+impl EofMode {
+	/// Returns [`None`] for [`EofMode::Unchanged`], as there is no single value to store.
+	#[must_use]
+	pub const fn value(self, cell_width: CellWidth) -> Option<u32> {
+		match self {
+			Self::Zero => Some(0),
+			Self::Max => Some(cell_width.max()),
+			Self::Newline => Some(b'\n' as u32),
+			Self::Unchanged => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+	tape_size: usize,
+	cell_width: CellWidth,
+	overflow: CellOverflow,
+	boundary: TapeBoundary,
+	eof: EofMode,
+	output_buffer_size: usize,
+	step_limit: Option<u64>,
+	timeout: Option<Duration>,
+	strict_semantics: bool,
+}
+
+impl Spec {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			tape_size: TAPE_SIZE,
+			cell_width: CellWidth::U8,
+			overflow: CellOverflow::Wrap,
+			boundary: TapeBoundary::Wrap,
+			eof: EofMode::Newline,
+			output_buffer_size: OUTPUT_BUFFER_SIZE,
+			step_limit: None,
+			timeout: None,
+			strict_semantics: false,
+		}
+	}
+
+	/// Returns [`None`] if `tape_size` is not a power of two.
+	#[must_use]
+	pub const fn with_tape_size(self, tape_size: usize) -> Option<Self> {
+		if tape_size.is_power_of_two() {
+			Some(Self { tape_size, ..self })
+		} else {
+			None
+		}
+	}
+
+	#[must_use]
+	pub const fn with_cell_width(self, cell_width: CellWidth) -> Self {
+		Self { cell_width, ..self }
+	}
+
+	#[must_use]
+	pub const fn with_overflow(self, overflow: CellOverflow) -> Self {
+		Self { overflow, ..self }
+	}
+
+	#[must_use]
+	pub const fn with_boundary(self, boundary: TapeBoundary) -> Self {
+		Self { boundary, ..self }
+	}
+
+	#[must_use]
+	pub const fn with_eof(self, eof: EofMode) -> Self {
+		Self { eof, ..self }
+	}
+
+	/// Output is flushed once this many bytes are buffered, 0 flushes every byte.
+	#[must_use]
+	pub const fn with_output_buffer_size(self, output_buffer_size: usize) -> Self {
+		Self {
+			output_buffer_size,
+			..self
+		}
+	}
+
+	/// Stops the program once its loops have jumped back to their start this many times.
+	#[must_use]
+	pub const fn with_step_limit(self, step_limit: u64) -> Self {
+		Self {
+			step_limit: Some(step_limit),
+			..self
+		}
+	}
+
+	/// Stops the program once it has been running for this long, only checked on loop iterations.
+	#[must_use]
+	pub const fn with_timeout(self, timeout: Duration) -> Self {
+		Self {
+			timeout: Some(timeout),
+			..self
+		}
+	}
+
+	/// Only allows optimizations that behave exactly like the unoptimized program, including
+	/// never terminating and the final state of the tape, on any starting tape.
+	#[must_use]
+	pub const fn with_strict_semantics(self, strict_semantics: bool) -> Self {
+		Self {
+			strict_semantics,
+			..self
+		}
+	}
+
+	#[must_use]
+	pub const fn tape_size(self) -> usize {
+		self.tape_size
+	}
+
+	#[must_use]
+	pub const fn cell_width(self) -> CellWidth {
+		self.cell_width
+	}
+
+	#[must_use]
+	pub const fn overflow(self) -> CellOverflow {
+		self.overflow
+	}
+
+	#[must_use]
+	pub const fn boundary(self) -> TapeBoundary {
+		self.boundary
+	}
+
+	#[must_use]
+	pub const fn eof(self) -> EofMode {
+		self.eof
+	}
+
+	/// The value an input instruction stores once input has run out, see [`EofMode::value`].
+	#[must_use]
+	pub const fn eof_value(self) -> Option<u32> {
+		self.eof.value(self.cell_width)
+	}
+
+	#[must_use]
+	pub const fn output_buffer_size(self) -> usize {
+		self.output_buffer_size
+	}
+
+	#[must_use]
+	pub const fn step_limit(self) -> Option<u64> {
+		self.step_limit
+	}
+
+	#[must_use]
+	pub const fn timeout(self) -> Option<Duration> {
+		self.timeout
+	}
+
+	#[must_use]
+	pub const fn strict_semantics(self) -> bool {
+		self.strict_semantics
+	}
+
+	/// Whether loops have to be instrumented to enforce a step limit or timeout.
+	#[must_use]
+	pub const fn is_metered(self) -> bool {
+		self.step_limit.is_some() || self.timeout.is_some()
+	}
+}
+
+impl Default for Spec {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,516 @@
+use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::{CellWidth, Spec, TapeBoundary};
+
+use super::{SourceWriter, TranspileError};
+
+const INCLUDES: &str = "#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>";
+
+const WRAP_TAPE: &str = "static cell_t tape[TAPE_SIZE];
+static ptrdiff_t p;
+
+/* TAPE_SIZE is a power of two, so masking wraps the pointer around either end */
+#define CELL(o) tape[(size_t)(p + (o)) & (TAPE_SIZE - 1)]";
+
+const OUT_OF_BOUNDS: &str = "static void out_of_bounds(ptrdiff_t index) {
+	fflush(stdout);
+	fprintf(stderr, \"error: tape pointer moved out of bounds to %td\\n\", index);
+	exit(1);
+}";
+
+const CHECKED_CELLS: &str = "static void cell_overflow(const char *message, size_t offset) {
+	fflush(stdout);
+	fprintf(stderr, \"error: %s at offset %zu\\n\", message, offset);
+	exit(1);
+}
+
+/* folded steps can be wider than a cell, which always overflows */
+static void add_cell(ptrdiff_t o, uint32_t value, size_t offset) {
+	if (value > (cell_t)-1 || CELL(o) > (cell_t)-1 - value) {
+		cell_overflow(\"cell incremented past its maximum value\", offset);
+	}
+
+	CELL(o) += (cell_t)value;
+}
+
+static void sub_cell(ptrdiff_t o, uint32_t value, size_t offset) {
+	if (CELL(o) < value) {
+		cell_overflow(\"cell decremented below zero\", offset);
+	}
+
+	CELL(o) -= (cell_t)value;
+}";
+
+const ABORT_TAPE: &str = "static cell_t tape[TAPE_SIZE];
+static ptrdiff_t p;
+
+static cell_t *cell(ptrdiff_t index) {
+	if (index < 0 || (size_t)index >= TAPE_SIZE) {
+		out_of_bounds(index);
+	}
+
+	return &tape[index];
+}
+
+#define CELL(o) (*cell(p + (o)))";
+
+const GROW_TAPE: &str = "static cell_t *tape;
+static size_t tape_len = TAPE_SIZE;
+static ptrdiff_t p;
+
+static cell_t *cell(ptrdiff_t index) {
+	if (index < 0) {
+		out_of_bounds(index);
+	}
+
+	if ((size_t)index >= tape_len) {
+		size_t new_len = tape_len;
+
+		while (new_len <= (size_t)index) {
+			new_len *= 2;
+		}
+
+		cell_t *new_tape = realloc(tape, new_len * sizeof *tape);
+
+		if (!new_tape) {
+			fputs(\"error: failed to grow the tape\\n\", stderr);
+			abort();
+		}
+
+		memset(new_tape + tape_len, 0, (new_len - tape_len) * sizeof *tape);
+		tape = new_tape;
+		tape_len = new_len;
+	}
+
+	return &tape[index];
+}
+
+/* the tape may move while growing, so never hold a cell across another access, helpers take
+ * offsets from the pointer instead */
+#define CELL(o) (*cell(p + (o)))";
+
+/// Turns optimized operations into a standalone C program reading from stdin and writing to
+/// stdout.
+#[tracing::instrument(skip_all)]
+pub fn emit_c(ops: &[BrainOperation], spec: Spec) -> Result<String, TranspileError> {
+	if spec.is_metered() {
+		return Err(TranspileError::Metered);
+	}
+
+	let mut emitter = CEmitter {
+		writer: SourceWriter::new(),
+		spec,
+	};
+
+	emitter.prelude();
+	emitter.main(ops)?;
+
+	Ok(emitter.writer.finish())
+}
+
+struct CEmitter {
+	writer: SourceWriter,
+	spec: Spec,
+}
+
+impl CEmitter {
+	fn prelude(&mut self) {
+		self.writer.line("/* generated by frick */");
+		self.writer.blank();
+		self.writer.block(INCLUDES);
+		self.writer.blank();
+		self.writer.line(format_args!(
+			"#define TAPE_SIZE ((size_t){})",
+			self.spec.tape_size()
+		));
+		self.writer.line(format_args!(
+			"typedef {} cell_t;",
+			match self.spec.cell_width() {
+				CellWidth::U8 => "uint8_t",
+				CellWidth::U16 => "uint16_t",
+				CellWidth::U32 => "uint32_t",
+			}
+		));
+		self.writer.blank();
+
+		match self.spec.boundary() {
+			TapeBoundary::Wrap => self.writer.block(WRAP_TAPE),
+			TapeBoundary::Abort => {
+				self.writer.block(OUT_OF_BOUNDS);
+				self.writer.blank();
+				self.writer.block(ABORT_TAPE);
+			}
+			TapeBoundary::Grow => {
+				self.writer.block(OUT_OF_BOUNDS);
+				self.writer.blank();
+				self.writer.block(GROW_TAPE);
+			}
+		}
+
+		if self.spec.overflow().is_trapping() {
+			self.writer.blank();
+			self.writer.block(CHECKED_CELLS);
+		}
+
+		self.writer.blank();
+		self.writer.line("static void read_cell(ptrdiff_t o) {");
+		self.writer.indent();
+		self.writer.line("fflush(stdout);");
+		self.writer.blank();
+		self.writer.line("int c = getchar();");
+		self.writer.blank();
+		self.writer.line("if (c != EOF) {");
+		self.writer.indent();
+		self.writer.line("CELL(o) = (cell_t)c;");
+		self.writer.dedent();
+
+		match self.spec.eof_value() {
+			Some(eof_value) => {
+				self.writer.line("} else {");
+				self.writer.indent();
+				self.writer.line(format_args!("CELL(o) = {eof_value};"));
+				self.writer.dedent();
+				self.writer.line("}");
+			}
+			None => self.writer.line("}"),
+		}
+
+		self.writer.dedent();
+		self.writer.line("}");
+		self.writer.blank();
+	}
+
+	fn main(&mut self, ops: &[BrainOperation]) -> Result<(), TranspileError> {
+		self.writer.line("int main(void) {");
+		self.writer.indent();
+
+		match self.spec.output_buffer_size() {
+			0 => self.writer.line("setvbuf(stdout, NULL, _IONBF, 0);"),
+			size => self
+				.writer
+				.line(format_args!("setvbuf(stdout, NULL, _IOFBF, {size});")),
+		}
+
+		if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			self.writer.blank();
+			self.writer.line("tape = calloc(TAPE_SIZE, sizeof *tape);");
+			self.writer.blank();
+			self.writer.line("if (!tape) {");
+			self.writer.indent();
+			self.writer.line("return 1;");
+			self.writer.dedent();
+			self.writer.line("}");
+		}
+
+		self.writer.blank();
+
+		self.ops(ops)?;
+
+		self.writer.blank();
+		self.writer.line("fflush(stdout);");
+
+		if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			self.writer.line("free(tape);");
+		}
+
+		self.writer.line("return 0;");
+		self.writer.dedent();
+		self.writer.line("}");
+
+		Ok(())
+	}
+
+	fn ops(&mut self, ops: &[BrainOperation]) -> Result<(), TranspileError> {
+		for op in ops {
+			match *op.op() {
+				BrainOperationType::IncrementCell(CellOffsetOptions { value, offset })
+					if self.spec.overflow().is_trapping() =>
+				{
+					self.writer.line(format_args!(
+						"add_cell({offset}, {value}, {});",
+						op.span().start
+					));
+				}
+				BrainOperationType::DecrementCell(CellOffsetOptions { value, offset })
+					if self.spec.overflow().is_trapping() =>
+				{
+					self.writer.line(format_args!(
+						"sub_cell({offset}, {value}, {});",
+						op.span().start
+					));
+				}
+				BrainOperationType::IncrementCell(CellOffsetOptions { value, offset }) => {
+					self.writer.line(format_args!("CELL({offset}) += {value};"));
+				}
+				BrainOperationType::DecrementCell(CellOffsetOptions { value, offset }) => {
+					self.writer.line(format_args!("CELL({offset}) -= {value};"));
+				}
+				BrainOperationType::SetCell(CellOffsetOptions { value, offset }) => {
+					self.writer.line(format_args!("CELL({offset}) = {value};"));
+				}
+				BrainOperationType::MovePointer(offset) => self.move_pointer(offset),
+				BrainOperationType::MoveCellValue(CellOffsetOptions { value, offset }) => {
+					self.move_value(value, offset);
+				}
+				BrainOperationType::TakeCellValue(CellOffsetOptions { value, offset }) => {
+					self.move_value(value, offset);
+					self.move_pointer(offset);
+				}
+				BrainOperationType::InputIntoCell => self.writer.line("read_cell(0);"),
+				BrainOperationType::OutputCell(CellOffsetOptions { value: 0, offset }) => {
+					self.writer.line(format_args!("putchar(CELL({offset}));"));
+				}
+				BrainOperationType::OutputCell(CellOffsetOptions { value, offset }) => {
+					self.writer.line(format_args!(
+						"putchar((uint8_t)(CELL({offset}) + {value}));"
+					));
+				}
+				BrainOperationType::OutputValue(value) => {
+					if value.is_ascii_graphic() || value == b' ' {
+						self.writer.line(format_args!(
+							"putchar({value}); /* '{}' */",
+							value.escape_ascii()
+						));
+					} else {
+						self.writer.line(format_args!("putchar({value});"));
+					}
+				}
+				BrainOperationType::DynamicLoop(ref ops) => {
+					self.writer.line("while (CELL(0)) {");
+					self.writer.indent();
+					self.ops(ops)?;
+					self.writer.dedent();
+					self.writer.line("}");
+				}
+				BrainOperationType::Comment(..) => {}
+				ref op => return Err(TranspileError::NotImplemented(op.clone())),
+			}
+		}
+
+		Ok(())
+	}
+
+	fn move_pointer(&mut self, offset: i32) {
+		if offset < 0 {
+			self.writer
+				.line(format_args!("p -= {};", offset.unsigned_abs()));
+		} else {
+			self.writer.line(format_args!("p += {offset};"));
+		}
+	}
+
+	fn move_value(&mut self, factor: u32, offset: i32) {
+		let scaled = match factor {
+			1 => String::new(),
+			// unsigned, so wide cells promoted to int can't overflow
+			factor => format!(" * {factor}u"),
+		};
+
+		if matches!(self.spec.boundary(), TapeBoundary::Grow) {
+			self.writer.line("{");
+			self.writer.indent();
+			self.writer.line("cell_t value = CELL(0);");
+			self.writer.line("CELL(0) = 0;");
+			self.writer
+				.line(format_args!("CELL({offset}) += value{scaled};"));
+			self.writer.dedent();
+			self.writer.line("}");
+		} else {
+			self.writer
+				.line(format_args!("CELL({offset}) += CELL(0){scaled};"));
+			self.writer.line("CELL(0) = 0;");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		env, fs,
+		process::{self, Command, Output},
+	};
+
+	use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+	use frick_spec::{CellOverflow, Spec, TapeBoundary};
+
+	use super::emit_c;
+
+	const SNAPSHOTS: &[(TapeBoundary, CellOverflow, &str)] = &[
+		(
+			TapeBoundary::Wrap,
+			CellOverflow::Wrap,
+			include_str!("snapshots/wrap.c"),
+		),
+		(
+			TapeBoundary::Wrap,
+			CellOverflow::Trap,
+			include_str!("snapshots/wrap_trap.c"),
+		),
+		(
+			TapeBoundary::Abort,
+			CellOverflow::Wrap,
+			include_str!("snapshots/abort.c"),
+		),
+		(
+			TapeBoundary::Abort,
+			CellOverflow::Trap,
+			include_str!("snapshots/abort_trap.c"),
+		),
+		(
+			TapeBoundary::Grow,
+			CellOverflow::Wrap,
+			include_str!("snapshots/grow.c"),
+		),
+		(
+			TapeBoundary::Grow,
+			CellOverflow::Trap,
+			include_str!("snapshots/grow_trap.c"),
+		),
+	];
+
+	fn ops() -> Vec<BrainOperation> {
+		let mut ops = frick_operations::parse_str(",[->+<]>.").unwrap();
+
+		ops.push(BrainOperation::new(
+			BrainOperationType::MoveCellValue(CellOffsetOptions::new(2, -1)),
+			9..15,
+		));
+
+		ops
+	}
+
+	/// Returns [`None`] when there's no C compiler to build the output with.
+	fn compile_and_run(name: &str, ops: &[BrainOperation], spec: Spec) -> Option<Output> {
+		let cc = env::var_os("CC").unwrap_or_else(|| "cc".into());
+
+		if Command::new(&cc).arg("--version").output().is_err() {
+			return None;
+		}
+
+		let dir = env::temp_dir().join(format!("frick-c-{name}-{}", process::id()));
+
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("main.c"), emit_c(ops, spec).unwrap()).unwrap();
+
+		let compiled = Command::new(&cc)
+			.current_dir(&dir)
+			.args(["-std=c99", "-Wall", "-Werror", "-Wno-unused-function"])
+			.args(["main.c", "-o", "main"])
+			.output()
+			.unwrap();
+
+		assert!(
+			compiled.status.success(),
+			"{}",
+			String::from_utf8_lossy(&compiled.stderr)
+		);
+
+		let output = Command::new(dir.join("main")).output().unwrap();
+
+		_ = fs::remove_dir_all(&dir);
+
+		Some(output)
+	}
+
+	#[test]
+	fn output_matches_snapshots() {
+		let ops = ops();
+
+		for &(boundary, overflow, expected) in SNAPSHOTS {
+			let spec = Spec::new().with_boundary(boundary).with_overflow(overflow);
+
+			assert_eq!(
+				emit_c(&ops, spec).unwrap(),
+				expected,
+				"{boundary:?} boundary with {overflow:?} overflow"
+			);
+		}
+	}
+
+	#[test]
+	fn hello_world_runs() {
+		let ops = frick_operations::parse_str(
+			&fs::read_to_string(concat!(
+				env!("CARGO_MANIFEST_DIR"),
+				"/../../programs/hello_world.bf"
+			))
+			.unwrap(),
+		)
+		.unwrap();
+
+		for &(boundary, overflow, _) in SNAPSHOTS {
+			let spec = Spec::new().with_boundary(boundary).with_overflow(overflow);
+			let name = format!("hello-{boundary:?}-{overflow:?}");
+
+			let Some(output) = compile_and_run(&name, &ops, spec) else {
+				return;
+			};
+
+			assert!(output.status.success(), "{name}");
+			assert_eq!(output.stdout, b"Hello World!\n", "{name}");
+		}
+	}
+
+	#[test]
+	fn increment_past_max_traps() {
+		let ops = frick_operations::parse_str(&"+".repeat(256)).unwrap();
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		let Some(output) = compile_and_run("increment", &ops, spec) else {
+			return;
+		};
+
+		assert!(!output.status.success());
+		assert!(
+			String::from_utf8_lossy(&output.stderr)
+				.contains("cell incremented past its maximum value at offset 255")
+		);
+	}
+
+	#[test]
+	fn step_wider_than_a_cell_traps() {
+		let ops = [BrainOperation::new(
+			BrainOperationType::IncrementCell(CellOffsetOptions::new(300, 0)),
+			4..5,
+		)];
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		let Some(output) = compile_and_run("wide", &ops, spec) else {
+			return;
+		};
+
+		assert!(!output.status.success());
+		assert!(
+			String::from_utf8_lossy(&output.stderr)
+				.contains("cell incremented past its maximum value at offset 4")
+		);
+	}
+
+	#[test]
+	fn trapping_add_grows_the_tape() {
+		let ops = [
+			BrainOperation::new(
+				BrainOperationType::IncrementCell(CellOffsetOptions::new(65, 100_000)),
+				0..1,
+			),
+			BrainOperation::new(BrainOperationType::MovePointer(100_000), 1..2),
+			BrainOperation::new(
+				BrainOperationType::OutputCell(CellOffsetOptions::new(0, 0)),
+				2..3,
+			),
+		];
+		let spec = Spec::new()
+			.with_boundary(TapeBoundary::Grow)
+			.with_overflow(CellOverflow::Trap);
+
+		let Some(output) = compile_and_run("grow", &ops, spec) else {
+			return;
+		};
+
+		assert!(output.status.success());
+		assert_eq!(output.stdout, b"A");
+	}
+}
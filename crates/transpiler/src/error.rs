@@ -0,0 +1,31 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+use frick_operations::BrainOperationType;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranspileError {
+	NotImplemented(BrainOperationType),
+	/// Step limits and timeouts are enforced by the frick runtime, which generated source
+	/// doesn't link against.
+	Metered,
+}
+
+impl Display for TranspileError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::NotImplemented(op) => {
+				f.write_str("operation ")?;
+				Debug::fmt(&op, f)?;
+				f.write_str(" is not implemented")
+			}
+			Self::Metered => {
+				f.write_str("step limits and timeouts are not supported by generated source")
+			}
+		}
+	}
+}
+
+impl StdError for TranspileError {}
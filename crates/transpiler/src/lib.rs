@@ -0,0 +1,9 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod c;
+mod error;
+mod rust;
+mod writer;
+
+use self::writer::SourceWriter;
+pub use self::{c::emit_c, error::TranspileError, rust::emit_rust};
@@ -0,0 +1,498 @@
+use std::fmt::Display;
+
+use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+use frick_spec::{CellWidth, Spec, TapeBoundary};
+
+use super::{SourceWriter, TranspileError};
+
+const WRAP_CELL: &str = "fn cell(&mut self, offset: isize) -> &mut Cell {
+	// TAPE_SIZE is a power of two, so masking wraps the pointer around either end
+	let index = self.pointer.wrapping_add(offset) as usize & (TAPE_SIZE - 1);
+
+	&mut self.cells[index]
+}";
+
+const ABORT_CELL: &str = "fn cell(&mut self, offset: isize) -> &mut Cell {
+	let index = self.pointer + offset;
+
+	match usize::try_from(index).ok().and_then(|i| self.cells.get_mut(i)) {
+		Some(cell) => cell,
+		None => panic!(\"tape pointer moved out of bounds to {index}\"),
+	}
+}";
+
+const GROW_CELL: &str = "fn cell(&mut self, offset: isize) -> &mut Cell {
+	let index = self.pointer + offset;
+
+	let Ok(index) = usize::try_from(index) else {
+		panic!(\"tape pointer moved out of bounds to {index}\");
+	};
+
+	if index >= self.cells.len() {
+		self.cells.resize((index + 1).next_power_of_two(), 0);
+	}
+
+	&mut self.cells[index]
+}";
+
+const TAPE_METHODS: &str = "fn get(&mut self, offset: isize) -> Cell {
+	*self.cell(offset)
+}
+
+fn set(&mut self, offset: isize, value: Cell) {
+	*self.cell(offset) = value;
+}
+
+fn add(&mut self, offset: isize, value: Cell) {
+	let cell = self.cell(offset);
+	*cell = cell.wrapping_add(value);
+}
+
+fn sub(&mut self, offset: isize, value: Cell) {
+	let cell = self.cell(offset);
+	*cell = cell.wrapping_sub(value);
+}
+
+fn move_value(&mut self, offset: isize, factor: Cell) {
+	let value = self.get(0);
+	self.set(0, 0);
+	self.add(offset, value.wrapping_mul(factor));
+}";
+
+const CHECKED_TAPE_METHODS: &str =
+	"// folded steps can be wider than a cell, which always overflows
+fn checked_add(&mut self, offset: isize, value: u32, at: usize) {
+	let cell = self.cell(offset);
+
+	match Cell::try_from(value).ok().and_then(|value| cell.checked_add(value)) {
+		Some(value) => *cell = value,
+		None => panic!(\"cell incremented past its maximum value at offset {at}\"),
+	}
+}
+
+fn checked_sub(&mut self, offset: isize, value: u32, at: usize) {
+	let cell = self.cell(offset);
+
+	match Cell::try_from(value).ok().and_then(|value| cell.checked_sub(value)) {
+		Some(value) => *cell = value,
+		None => panic!(\"cell decremented below zero at offset {at}\"),
+	}
+}";
+
+const READ: &str =
+	"fn read(input: &mut impl Read, output: &mut impl Write) -> io::Result<Option<u8>> {
+	// programs are usually prompting for whatever comes next
+	output.flush()?;
+
+	let mut value = 0;
+
+	match input.read_exact(std::slice::from_mut(&mut value)) {
+		Ok(()) => Ok(Some(value)),
+		Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+		Err(e) => Err(e),
+	}
+}";
+
+/// Turns optimized operations into a self-contained Rust module exposing
+/// `pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()>`.
+///
+/// The module has no dependencies outside of `std`, so it can be written out by a build script
+/// and pulled in with `include!`.
+#[tracing::instrument(skip_all)]
+pub fn emit_rust(ops: &[BrainOperation], spec: Spec) -> Result<String, TranspileError> {
+	if spec.is_metered() {
+		return Err(TranspileError::Metered);
+	}
+
+	let mut emitter = RustEmitter {
+		writer: SourceWriter::new(),
+		spec,
+	};
+
+	emitter.prelude();
+	emitter.run(ops)?;
+
+	Ok(emitter.writer.finish())
+}
+
+struct RustEmitter {
+	writer: SourceWriter,
+	spec: Spec,
+}
+
+impl RustEmitter {
+	fn prelude(&mut self) {
+		self.writer.line("// generated by frick");
+		self.writer.blank();
+		self.writer.line("use std::io::{self, Read, Write};");
+		self.writer.blank();
+		self.writer.line(format_args!(
+			"const TAPE_SIZE: usize = {};",
+			self.spec.tape_size()
+		));
+		self.writer.line(format_args!(
+			"type Cell = {};",
+			match self.spec.cell_width() {
+				CellWidth::U8 => "u8",
+				CellWidth::U16 => "u16",
+				CellWidth::U32 => "u32",
+			}
+		));
+		self.writer.blank();
+
+		let (cells_type, new_cells, cell) = match self.spec.boundary() {
+			TapeBoundary::Wrap => (
+				"Box<[Cell]>",
+				"vec![0; TAPE_SIZE].into_boxed_slice()",
+				WRAP_CELL,
+			),
+			TapeBoundary::Abort => (
+				"Box<[Cell]>",
+				"vec![0; TAPE_SIZE].into_boxed_slice()",
+				ABORT_CELL,
+			),
+			TapeBoundary::Grow => ("Vec<Cell>", "vec![0; TAPE_SIZE]", GROW_CELL),
+		};
+
+		self.writer.line("struct Tape {");
+		self.writer.indent();
+		self.writer.line(format_args!("cells: {cells_type},"));
+		self.writer.line("pointer: isize,");
+		self.writer.dedent();
+		self.writer.line("}");
+		self.writer.blank();
+
+		self.writer.line("#[allow(dead_code)]");
+		self.writer.line("impl Tape {");
+		self.writer.indent();
+		self.writer.line("fn new() -> Self {");
+		self.writer.indent();
+		self.writer.line("Self {");
+		self.writer.indent();
+		self.writer.line(format_args!("cells: {new_cells},"));
+		self.writer.line("pointer: 0,");
+		self.writer.dedent();
+		self.writer.line("}");
+		self.writer.dedent();
+		self.writer.line("}");
+		self.writer.blank();
+		self.writer.block(cell);
+		self.writer.blank();
+		self.writer.block(TAPE_METHODS);
+
+		if self.spec.overflow().is_trapping() {
+			self.writer.blank();
+			self.writer.block(CHECKED_TAPE_METHODS);
+		}
+
+		self.writer.dedent();
+		self.writer.line("}");
+		self.writer.blank();
+		self.writer.block(READ);
+		self.writer.blank();
+	}
+
+	fn run(&mut self, ops: &[BrainOperation]) -> Result<(), TranspileError> {
+		self.writer
+			.line("pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {");
+		self.writer.indent();
+		self.writer.line(format_args!(
+			"let mut output = io::BufWriter::with_capacity({}, output);",
+			self.spec.output_buffer_size()
+		));
+		self.writer.line("let mut tape = Tape::new();");
+		self.writer.blank();
+
+		self.ops(ops)?;
+
+		self.writer.blank();
+		self.writer.line("output.flush()");
+		self.writer.dedent();
+		self.writer.line("}");
+
+		Ok(())
+	}
+
+	fn ops(&mut self, ops: &[BrainOperation]) -> Result<(), TranspileError> {
+		let mut ops = ops.iter().peekable();
+
+		while let Some(op) = ops.next() {
+			match *op.op() {
+				BrainOperationType::IncrementCell(CellOffsetOptions { value, offset })
+					if self.spec.overflow().is_trapping() =>
+				{
+					self.writer.line(format_args!(
+						"tape.checked_add({offset}, {value}, {});",
+						op.span().start
+					));
+				}
+				BrainOperationType::DecrementCell(CellOffsetOptions { value, offset })
+					if self.spec.overflow().is_trapping() =>
+				{
+					self.writer.line(format_args!(
+						"tape.checked_sub({offset}, {value}, {});",
+						op.span().start
+					));
+				}
+				BrainOperationType::IncrementCell(CellOffsetOptions { value, offset }) => {
+					self.writer
+						.line(format_args!("tape.add({offset}, {value});"));
+				}
+				BrainOperationType::DecrementCell(CellOffsetOptions { value, offset }) => {
+					self.writer
+						.line(format_args!("tape.sub({offset}, {value});"));
+				}
+				BrainOperationType::SetCell(CellOffsetOptions { value, offset }) => {
+					self.writer
+						.line(format_args!("tape.set({offset}, {value});"));
+				}
+				BrainOperationType::MovePointer(offset) => self.move_pointer(offset),
+				BrainOperationType::MoveCellValue(CellOffsetOptions { value, offset }) => {
+					self.writer
+						.line(format_args!("tape.move_value({offset}, {value});"));
+				}
+				BrainOperationType::TakeCellValue(CellOffsetOptions { value, offset }) => {
+					self.writer
+						.line(format_args!("tape.move_value({offset}, {value});"));
+					self.move_pointer(offset);
+				}
+				BrainOperationType::InputIntoCell => {
+					if let Some(eof_value) = self.spec.eof_value() {
+						self.writer.line(format_args!(
+							"tape.set(0, read(input, &mut output)?.map_or({eof_value}, Cell::from));"
+						));
+					} else {
+						self.writer
+							.line("if let Some(value) = read(input, &mut output)? {");
+						self.writer.indent();
+						self.writer.line("tape.set(0, value.into());");
+						self.writer.dedent();
+						self.writer.line("}");
+					}
+				}
+				BrainOperationType::OutputCell(CellOffsetOptions { value: 0, offset }) => {
+					let cell = self.low_byte(format_args!("tape.get({offset})"));
+
+					self.writer
+						.line(format_args!("output.write_all(&[{cell}])?;"));
+				}
+				BrainOperationType::OutputCell(CellOffsetOptions { value, offset }) => {
+					let cell =
+						self.low_byte(format_args!("tape.get({offset}).wrapping_add({value})"));
+
+					self.writer
+						.line(format_args!("output.write_all(&[{cell}])?;"));
+				}
+				BrainOperationType::OutputValue(value) => {
+					// runs of constant output read much better as a single string
+					let mut bytes = vec![value];
+
+					while let Some(&BrainOperationType::OutputValue(value)) =
+						ops.peek().map(|op| op.op())
+					{
+						bytes.push(value);
+						ops.next();
+					}
+
+					self.writer.line(format_args!(
+						"output.write_all(b\"{}\")?;",
+						bytes.escape_ascii()
+					));
+				}
+				BrainOperationType::DynamicLoop(ref ops) => {
+					self.writer.line("while tape.get(0) != 0 {");
+					self.writer.indent();
+					self.ops(ops)?;
+					self.writer.dedent();
+					self.writer.line("}");
+				}
+				BrainOperationType::Comment(..) => {}
+				ref op => return Err(TranspileError::NotImplemented(op.clone())),
+			}
+		}
+
+		Ok(())
+	}
+
+	// only the low byte of a cell is ever output
+	fn low_byte(&self, cell: impl Display) -> String {
+		match self.spec.cell_width() {
+			CellWidth::U8 => cell.to_string(),
+			CellWidth::U16 | CellWidth::U32 => format!("{cell} as u8"),
+		}
+	}
+
+	fn move_pointer(&mut self, offset: i32) {
+		if offset < 0 {
+			self.writer
+				.line(format_args!("tape.pointer -= {};", offset.unsigned_abs()));
+		} else {
+			self.writer.line(format_args!("tape.pointer += {offset};"));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		env,
+		fmt::Write as _,
+		fs,
+		process::{self, Command, Output},
+	};
+
+	use frick_operations::{BrainOperation, BrainOperationType, CellOffsetOptions};
+	use frick_spec::{CellOverflow, CellWidth, EofMode, Spec, TapeBoundary};
+
+	use super::emit_rust;
+
+	const MAIN: &str = "
+fn main() {
+	run(&mut io::stdin(), &mut io::stdout()).unwrap();
+}
+";
+
+	fn every_spec() -> impl Iterator<Item = Spec> {
+		[CellWidth::U8, CellWidth::U16, CellWidth::U32]
+			.into_iter()
+			.flat_map(|width| {
+				[TapeBoundary::Wrap, TapeBoundary::Abort, TapeBoundary::Grow]
+					.map(|boundary| Spec::new().with_cell_width(width).with_boundary(boundary))
+			})
+			.flat_map(|spec| {
+				[CellOverflow::Wrap, CellOverflow::Trap].map(|o| spec.with_overflow(o))
+			})
+			.flat_map(|spec| {
+				[
+					EofMode::Zero,
+					EofMode::Max,
+					EofMode::Newline,
+					EofMode::Unchanged,
+				]
+				.map(|eof| spec.with_eof(eof))
+			})
+	}
+
+	fn compile_and_run(name: &str, ops: &[BrainOperation], spec: Spec) -> Option<Output> {
+		compile_and_run_source(name, emit_rust(ops, spec).unwrap() + MAIN)
+	}
+
+	/// Returns [`None`] when there's no `rustc` to build the output with.
+	fn compile_and_run_source(name: &str, source: String) -> Option<Output> {
+		let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+
+		if Command::new(&rustc).arg("--version").output().is_err() {
+			return None;
+		}
+
+		let dir = env::temp_dir().join(format!("frick-rust-{name}-{}", process::id()));
+
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("main.rs"), source).unwrap();
+
+		let compiled = Command::new(&rustc)
+			.current_dir(&dir)
+			.args(["--edition", "2021", "main.rs", "-o", "main"])
+			.output()
+			.unwrap();
+
+		assert!(
+			compiled.status.success(),
+			"{}",
+			String::from_utf8_lossy(&compiled.stderr)
+		);
+
+		let output = Command::new(dir.join("main")).output().unwrap();
+
+		_ = fs::remove_dir_all(&dir);
+
+		Some(output)
+	}
+
+	#[test]
+	fn every_spec_compiles_and_runs() {
+		let hello_world = frick_operations::parse_str(
+			&fs::read_to_string(concat!(
+				env!("CARGO_MANIFEST_DIR"),
+				"/../../programs/hello_world.bf"
+			))
+			.unwrap(),
+		)
+		.unwrap();
+
+		// touches every tape method, including the ones hello world never needs
+		let mut everything = frick_operations::parse_str(",[->+<]>.").unwrap();
+		everything.push(BrainOperation::new(
+			BrainOperationType::MoveCellValue(CellOffsetOptions::new(2, -1)),
+			9..15,
+		));
+
+		let mut source = String::new();
+		let mut main = String::from("fn main() {\n");
+
+		// a single crate keeps this to one rustc invocation
+		for (i, spec) in every_spec().enumerate() {
+			for (name, ops) in [("hello", &hello_world), ("everything", &everything)] {
+				writeln!(source, "// {spec:?}\nmod {name}_{i} {{").unwrap();
+				source += &emit_rust(ops, spec).unwrap();
+				source += "}\n\n";
+			}
+
+			writeln!(
+				main,
+				"\thello_{i}::run(&mut io::empty(), &mut io::stdout()).unwrap();\n\
+				 \teverything_{i}::run(&mut &b\"\\x02\"[..], &mut io::sink()).unwrap();"
+			)
+			.unwrap();
+		}
+
+		main += "}\n";
+
+		let Some(output) =
+			compile_and_run_source("every-spec", source + "use std::io;\n\n" + &main)
+		else {
+			return;
+		};
+
+		assert!(output.status.success());
+		assert_eq!(
+			output.stdout,
+			b"Hello World!\n".repeat(every_spec().count())
+		);
+	}
+
+	#[test]
+	fn increment_past_max_traps() {
+		let ops = frick_operations::parse_str(&"+".repeat(256)).unwrap();
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		let Some(output) = compile_and_run("increment", &ops, spec) else {
+			return;
+		};
+
+		assert!(!output.status.success());
+		assert!(
+			String::from_utf8_lossy(&output.stderr)
+				.contains("cell incremented past its maximum value at offset 255")
+		);
+	}
+
+	#[test]
+	fn step_wider_than_a_cell_traps() {
+		let ops = [BrainOperation::new(
+			BrainOperationType::IncrementCell(CellOffsetOptions::new(300, 0)),
+			4..5,
+		)];
+		let spec = Spec::new().with_overflow(CellOverflow::Trap);
+
+		let Some(output) = compile_and_run("wide", &ops, spec) else {
+			return;
+		};
+
+		assert!(!output.status.success());
+		assert!(
+			String::from_utf8_lossy(&output.stderr)
+				.contains("cell incremented past its maximum value at offset 4")
+		);
+	}
+}
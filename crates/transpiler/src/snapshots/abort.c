@@ -0,0 +1,60 @@
+/* generated by frick */
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define TAPE_SIZE ((size_t)32768)
+typedef uint8_t cell_t;
+
+static void out_of_bounds(ptrdiff_t index) {
+	fflush(stdout);
+	fprintf(stderr, "error: tape pointer moved out of bounds to %td\n", index);
+	exit(1);
+}
+
+static cell_t tape[TAPE_SIZE];
+static ptrdiff_t p;
+
+static cell_t *cell(ptrdiff_t index) {
+	if (index < 0 || (size_t)index >= TAPE_SIZE) {
+		out_of_bounds(index);
+	}
+
+	return &tape[index];
+}
+
+#define CELL(o) (*cell(p + (o)))
+
+static void read_cell(ptrdiff_t o) {
+	fflush(stdout);
+
+	int c = getchar();
+
+	if (c != EOF) {
+		CELL(o) = (cell_t)c;
+	} else {
+		CELL(o) = 10;
+	}
+}
+
+int main(void) {
+	setvbuf(stdout, NULL, _IOFBF, 8192);
+
+	read_cell(0);
+	while (CELL(0)) {
+		CELL(0) -= 1;
+		p += 1;
+		CELL(0) += 1;
+		p -= 1;
+	}
+	p += 1;
+	putchar(CELL(0));
+	CELL(-1) += CELL(0) * 2u;
+	CELL(0) = 0;
+
+	fflush(stdout);
+	return 0;
+}
@@ -0,0 +1,92 @@
+/* generated by frick */
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define TAPE_SIZE ((size_t)32768)
+typedef uint8_t cell_t;
+
+static void out_of_bounds(ptrdiff_t index) {
+	fflush(stdout);
+	fprintf(stderr, "error: tape pointer moved out of bounds to %td\n", index);
+	exit(1);
+}
+
+static cell_t *tape;
+static size_t tape_len = TAPE_SIZE;
+static ptrdiff_t p;
+
+static cell_t *cell(ptrdiff_t index) {
+	if (index < 0) {
+		out_of_bounds(index);
+	}
+
+	if ((size_t)index >= tape_len) {
+		size_t new_len = tape_len;
+
+		while (new_len <= (size_t)index) {
+			new_len *= 2;
+		}
+
+		cell_t *new_tape = realloc(tape, new_len * sizeof *tape);
+
+		if (!new_tape) {
+			fputs("error: failed to grow the tape\n", stderr);
+			abort();
+		}
+
+		memset(new_tape + tape_len, 0, (new_len - tape_len) * sizeof *tape);
+		tape = new_tape;
+		tape_len = new_len;
+	}
+
+	return &tape[index];
+}
+
+/* the tape may move while growing, so never hold a cell across another access, helpers take
+ * offsets from the pointer instead */
+#define CELL(o) (*cell(p + (o)))
+
+static void read_cell(ptrdiff_t o) {
+	fflush(stdout);
+
+	int c = getchar();
+
+	if (c != EOF) {
+		CELL(o) = (cell_t)c;
+	} else {
+		CELL(o) = 10;
+	}
+}
+
+int main(void) {
+	setvbuf(stdout, NULL, _IOFBF, 8192);
+
+	tape = calloc(TAPE_SIZE, sizeof *tape);
+
+	if (!tape) {
+		return 1;
+	}
+
+	read_cell(0);
+	while (CELL(0)) {
+		CELL(0) -= 1;
+		p += 1;
+		CELL(0) += 1;
+		p -= 1;
+	}
+	p += 1;
+	putchar(CELL(0));
+	{
+		cell_t value = CELL(0);
+		CELL(0) = 0;
+		CELL(-1) += value * 2u;
+	}
+
+	fflush(stdout);
+	free(tape);
+	return 0;
+}
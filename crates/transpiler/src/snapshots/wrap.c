@@ -0,0 +1,47 @@
+/* generated by frick */
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define TAPE_SIZE ((size_t)32768)
+typedef uint8_t cell_t;
+
+static cell_t tape[TAPE_SIZE];
+static ptrdiff_t p;
+
+/* TAPE_SIZE is a power of two, so masking wraps the pointer around either end */
+#define CELL(o) tape[(size_t)(p + (o)) & (TAPE_SIZE - 1)]
+
+static void read_cell(ptrdiff_t o) {
+	fflush(stdout);
+
+	int c = getchar();
+
+	if (c != EOF) {
+		CELL(o) = (cell_t)c;
+	} else {
+		CELL(o) = 10;
+	}
+}
+
+int main(void) {
+	setvbuf(stdout, NULL, _IOFBF, 8192);
+
+	read_cell(0);
+	while (CELL(0)) {
+		CELL(0) -= 1;
+		p += 1;
+		CELL(0) += 1;
+		p -= 1;
+	}
+	p += 1;
+	putchar(CELL(0));
+	CELL(-1) += CELL(0) * 2u;
+	CELL(0) = 0;
+
+	fflush(stdout);
+	return 0;
+}
@@ -0,0 +1,70 @@
+/* generated by frick */
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define TAPE_SIZE ((size_t)32768)
+typedef uint8_t cell_t;
+
+static cell_t tape[TAPE_SIZE];
+static ptrdiff_t p;
+
+/* TAPE_SIZE is a power of two, so masking wraps the pointer around either end */
+#define CELL(o) tape[(size_t)(p + (o)) & (TAPE_SIZE - 1)]
+
+static void cell_overflow(const char *message, size_t offset) {
+	fflush(stdout);
+	fprintf(stderr, "error: %s at offset %zu\n", message, offset);
+	exit(1);
+}
+
+/* folded steps can be wider than a cell, which always overflows */
+static void add_cell(ptrdiff_t o, uint32_t value, size_t offset) {
+	if (value > (cell_t)-1 || CELL(o) > (cell_t)-1 - value) {
+		cell_overflow("cell incremented past its maximum value", offset);
+	}
+
+	CELL(o) += (cell_t)value;
+}
+
+static void sub_cell(ptrdiff_t o, uint32_t value, size_t offset) {
+	if (CELL(o) < value) {
+		cell_overflow("cell decremented below zero", offset);
+	}
+
+	CELL(o) -= (cell_t)value;
+}
+
+static void read_cell(ptrdiff_t o) {
+	fflush(stdout);
+
+	int c = getchar();
+
+	if (c != EOF) {
+		CELL(o) = (cell_t)c;
+	} else {
+		CELL(o) = 10;
+	}
+}
+
+int main(void) {
+	setvbuf(stdout, NULL, _IOFBF, 8192);
+
+	read_cell(0);
+	while (CELL(0)) {
+		sub_cell(0, 1, 2);
+		p += 1;
+		add_cell(0, 1, 4);
+		p -= 1;
+	}
+	p += 1;
+	putchar(CELL(0));
+	CELL(-1) += CELL(0) * 2u;
+	CELL(0) = 0;
+
+	fflush(stdout);
+	return 0;
+}
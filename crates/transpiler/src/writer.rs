@@ -0,0 +1,50 @@
+use std::fmt::{Display, Write as _};
+
+/// Builds up indented source code one line at a time.
+#[derive(Debug, Default)]
+pub struct SourceWriter {
+	output: String,
+	depth: usize,
+}
+
+impl SourceWriter {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn line(&mut self, line: impl Display) {
+		for _ in 0..self.depth {
+			self.output.push('\t');
+		}
+
+		// writing into a string can't fail
+		_ = writeln!(self.output, "{line}");
+	}
+
+	pub fn blank(&mut self) {
+		self.output.push('\n');
+	}
+
+	/// Writes each line of `block` at the current depth, keeping its own indentation.
+	pub fn block(&mut self, block: &str) {
+		for line in block.lines() {
+			if line.is_empty() {
+				self.blank();
+			} else {
+				self.line(line);
+			}
+		}
+	}
+
+	pub const fn indent(&mut self) {
+		self.depth += 1;
+	}
+
+	pub const fn dedent(&mut self) {
+		self.depth -= 1;
+	}
+
+	pub fn finish(self) -> String {
+		self.output
+	}
+}
@@ -9,7 +9,7 @@ use core::{
 	marker::PhantomData,
 };
 
-use frick_spec::{POINTER_SIZE, TAPE_SIZE};
+use frick_spec::{POINTER_SIZE, Spec};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,9 +19,6 @@ pub struct Immediate {
 }
 
 impl Immediate {
-	pub const CELL_ZERO: Self = Self::cell(0);
-	pub const TAPE_SIZE_MINUS_ONE: Self = Self::pointer(TAPE_SIZE as u64 - 1);
-
 	#[must_use]
 	pub const fn new(value: u64, size: u32) -> Self {
 		Self { value, size }
@@ -33,8 +30,18 @@ impl Immediate {
 	}
 
 	#[must_use]
-	pub const fn cell(value: u64) -> Self {
-		Self::new(value, 8)
+	pub const fn tape_size_minus_one(spec: Spec) -> Self {
+		Self::pointer(spec.tape_size() as u64 - 1)
+	}
+
+	#[must_use]
+	pub const fn cell(value: u64, spec: Spec) -> Self {
+		Self::new(value, spec.cell_width().bits())
+	}
+
+	#[must_use]
+	pub const fn cell_zero(spec: Spec) -> Self {
+		Self::cell(0, spec)
 	}
 
 	#[must_use]
@@ -1,27 +1,430 @@
-use std::path::{Path, PathBuf};
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+	time::Duration,
+};
 
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+#[cfg(all(unix, feature = "llvm"))]
+use frick_assembler::Sandbox;
+use frick_operations::InitialTape;
+use frick_serialize::{Artifact, EmitOptions};
+use frick_spec::{
+	CellOverflow, CellWidth, EofMode, OUTPUT_BUFFER_SIZE, Spec, TAPE_SIZE, TapeBoundary,
+};
+
+use super::source::Source;
 
 #[derive(Debug, Clone, Parser)]
 pub struct Args {
-	pub file_path: PathBuf,
-	#[arg(short, long)]
-	pub output_path: PathBuf,
-	#[arg(short, long)]
-	pub passes_path: Option<PathBuf>,
+	#[command(subcommand)]
+	pub command: Command,
 }
 
-#[allow(unreachable_patterns)]
 impl Args {
-	pub fn file_path(&self) -> &Path {
-		&self.file_path
+	pub const fn command(&self) -> &Command {
+		&self.command
+	}
+
+	pub fn output_path(&self) -> Option<&Path> {
+		match self.command() {
+			Command::Run(args) => args.compile.output_path(),
+			#[cfg(feature = "llvm")]
+			Command::Build(args) => args.compile.output_path(),
+			Command::Emit(args) => args.compile.output_path(),
+			Command::Check(..) => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+	/// Execute the program with the JIT or the interpreter.
+	Run(RunArgs),
+	/// Compile the program into a standalone executable.
+	#[cfg(feature = "llvm")]
+	Build(BuildArgs),
+	/// Stop after the given stage and write only its artifact.
+	Emit(EmitArgs),
+	/// Parse and verify the program without invoking LLVM.
+	Check(CheckArgs),
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct SourceArgs {
+	/// The program to compile, read from stdin when neither this nor `-e` is given.
+	pub file_path: Option<PathBuf>,
+	#[arg(short, long, conflicts_with = "file_path")]
+	pub expression: Option<String>,
+}
+
+impl SourceArgs {
+	pub fn read(&self) -> io::Result<Source> {
+		Source::read(self.file_path.as_deref(), self.expression.as_deref())
 	}
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct SpecArgs {
+	/// The number of cells on the tape, must be a power of two.
+	#[arg(long, default_value_t = TAPE_SIZE, value_parser = parse_tape_size)]
+	pub tape_size: usize,
+	/// The number of bits in each cell.
+	#[arg(long, value_enum, default_value_t = Width::U8)]
+	pub cell_width: Width,
+	/// What happens when a cell is incremented past its maximum or decremented below zero.
+	#[arg(long, value_enum, default_value_t = Overflow::Wrap)]
+	pub overflow: Overflow,
+	/// What happens when the pointer moves off either end of the tape.
+	#[arg(long, value_enum, default_value_t = Boundary::Wrap)]
+	pub boundary: Boundary,
+	/// The value stored into a cell when input has run out.
+	#[arg(long, value_enum, default_value_t = Eof::Newline)]
+	pub eof: Eof,
+	/// How many bytes of output are buffered before being written, 0 writes every byte.
+	#[arg(long, default_value_t = OUTPUT_BUFFER_SIZE)]
+	pub output_buffer_size: usize,
+	/// The cell the pointer starts on.
+	#[arg(long, default_value_t = 0)]
+	pub start_pointer: usize,
+	/// A file whose bytes are placed onto the tape, one per cell, before the program starts.
+	#[arg(long, conflicts_with = "cells")]
+	pub tape_file: Option<PathBuf>,
+	/// Values placed onto the tape, one per cell, before the program starts.
+	#[arg(long, value_delimiter = ',')]
+	pub cells: Vec<u32>,
+	/// Fail to compile instead of warning when a loop is proven to never terminate.
+	#[arg(long)]
+	pub deny_infinite_loops: bool,
+	/// Only run optimizations that keep the program's behaviour exactly, including hanging.
+	#[arg(long)]
+	pub strict_semantics: bool,
+	/// Stop the program after this many loop iterations.
+	#[arg(long)]
+	pub step_limit: Option<u64>,
+	/// Stop the program after running for this many seconds.
+	#[arg(long, value_parser = parse_timeout)]
+	pub timeout: Option<Duration>,
+}
+
+impl SpecArgs {
+	pub fn spec(&self) -> Spec {
+		let mut spec = Spec::new()
+			.with_tape_size(self.tape_size)
+			.expect("tape size should be validated while parsing")
+			.with_cell_width(self.cell_width.into())
+			.with_overflow(self.overflow.into())
+			.with_boundary(self.boundary.into())
+			.with_eof(self.eof.into())
+			.with_output_buffer_size(self.output_buffer_size)
+			.with_strict_semantics(self.strict_semantics);
 
-	pub fn output_path(&self) -> &Path {
-		&self.output_path
+		if let Some(step_limit) = self.step_limit {
+			spec = spec.with_step_limit(step_limit);
+		}
+
+		if let Some(timeout) = self.timeout {
+			spec = spec.with_timeout(timeout);
+		}
+
+		spec
+	}
+
+	pub fn initial_tape(&self) -> io::Result<InitialTape> {
+		let initial_tape = InitialTape::new().with_pointer(self.start_pointer);
+
+		Ok(match &self.tape_file {
+			Some(tape_file) => {
+				initial_tape.with_cells(fs::read(tape_file)?.into_iter().map(u32::from))
+			}
+			None => initial_tape.with_cells(self.cells.iter().copied()),
+		})
+	}
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct CompileArgs {
+	#[command(flatten)]
+	pub source: SourceArgs,
+	#[command(flatten)]
+	pub spec: SpecArgs,
+	#[arg(short, long)]
+	pub output_path: Option<PathBuf>,
+	#[arg(short, long)]
+	pub passes_path: Option<PathBuf>,
+}
+
+impl CompileArgs {
+	pub fn output_path(&self) -> Option<&Path> {
+		self.output_path.as_deref()
 	}
 
 	pub fn passes_path(&self) -> Option<&Path> {
 		self.passes_path.as_deref()
 	}
+
+	pub fn emit_options(&self, stages: &[Stage]) -> EmitOptions<'_> {
+		match self.output_path() {
+			Some(output_path) => EmitOptions::new(
+				output_path,
+				stages.iter().copied().map(Artifact::from).collect(),
+			),
+			None => EmitOptions::none(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct RunArgs {
+	#[command(flatten)]
+	pub compile: CompileArgs,
+	#[arg(long, value_enum, value_delimiter = ',', requires = "output_path")]
+	pub emit: Vec<Stage>,
+	/// How the program is run, `interp` and `cranelift` skip LLVM entirely.
+	#[arg(long, value_enum, default_value_t)]
+	pub backend: Backend,
+	#[cfg(all(unix, feature = "llvm"))]
+	#[command(flatten)]
+	pub sandbox: SandboxArgs,
+}
+
+impl RunArgs {
+	pub fn emit_options(&self) -> EmitOptions<'_> {
+		self.compile.emit_options(&self.emit)
+	}
+}
+
+#[cfg(all(unix, feature = "llvm"))]
+#[derive(Debug, Clone, ClapArgs)]
+pub struct SandboxArgs {
+	/// Build the program and run it in a child process, reading all of stdin up front.
+	#[arg(long)]
+	pub sandbox: bool,
+	/// The most memory the sandboxed program may map, in bytes.
+	#[arg(long, requires = "sandbox")]
+	pub memory_limit: Option<u64>,
+	/// The most CPU time the sandboxed program may use, in seconds.
+	#[arg(long, requires = "sandbox", value_parser = parse_timeout)]
+	pub cpu_limit: Option<Duration>,
+	/// The most bytes the sandboxed program may write before being killed.
+	#[arg(long, requires = "sandbox")]
+	pub output_limit: Option<usize>,
+	/// The runtime library the sandboxed program is linked against.
+	#[arg(long, requires = "sandbox")]
+	pub runtime_path: Option<PathBuf>,
+}
+
+#[cfg(all(unix, feature = "llvm"))]
+impl SandboxArgs {
+	pub fn sandbox(&self) -> Option<Sandbox> {
+		if !self.sandbox {
+			return None;
+		}
+
+		let mut sandbox = Sandbox::new();
+
+		if let Some(memory_limit) = self.memory_limit {
+			sandbox = sandbox.with_memory_limit(memory_limit);
+		}
+
+		if let Some(cpu_limit) = self.cpu_limit {
+			sandbox = sandbox.with_cpu_limit(cpu_limit);
+		}
+
+		if let Some(output_limit) = self.output_limit {
+			sandbox = sandbox.with_output_limit(output_limit);
+		}
+
+		Some(sandbox)
+	}
+
+	pub fn runtime_path(&self) -> Option<&Path> {
+		self.runtime_path.as_deref()
+	}
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Debug, Clone, ClapArgs)]
+pub struct BuildArgs {
+	#[command(flatten)]
+	pub compile: CompileArgs,
+	#[arg(long, value_enum, value_delimiter = ',', requires = "output_path")]
+	pub emit: Vec<Stage>,
+	#[arg(short, long)]
+	pub build_path: PathBuf,
+	#[arg(long)]
+	pub runtime_path: Option<PathBuf>,
+	#[arg(long)]
+	pub linker: Option<PathBuf>,
+	/// Build a C-ABI library function with this name instead of an executable, writing the
+	/// object, static archive, and header into the build path.
+	#[arg(long, conflicts_with_all = ["runtime_path", "linker"])]
+	pub library: Option<String>,
+}
+
+#[cfg(feature = "llvm")]
+impl BuildArgs {
+	pub fn emit_options(&self) -> EmitOptions<'_> {
+		self.compile.emit_options(&self.emit)
+	}
+
+	pub fn build_path(&self) -> &Path {
+		&self.build_path
+	}
+
+	pub fn runtime_path(&self) -> Option<&Path> {
+		self.runtime_path.as_deref()
+	}
+
+	pub fn linker(&self) -> Option<&Path> {
+		self.linker.as_deref()
+	}
+
+	pub fn library(&self) -> Option<&str> {
+		self.library.as_deref()
+	}
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct EmitArgs {
+	#[command(flatten)]
+	pub compile: CompileArgs,
+	#[arg(short, long, value_enum)]
+	pub stage: Stage,
+}
+
+#[derive(Debug, Clone, ClapArgs)]
+pub struct CheckArgs {
+	#[command(flatten)]
+	pub source: SourceArgs,
+	#[command(flatten)]
+	pub spec: SpecArgs,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+	#[cfg(feature = "llvm")]
+	#[default]
+	Llvm,
+	#[cfg_attr(not(feature = "llvm"), default)]
+	Interp,
+	Cranelift,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Stage {
+	Ops,
+	Instrs,
+	LlvmIr,
+	Bitcode,
+	Obj,
+	Asm,
+	C,
+	Rust,
+}
+
+impl From<Stage> for Artifact {
+	fn from(value: Stage) -> Self {
+		match value {
+			Stage::Ops => Self::Operations,
+			Stage::Instrs => Self::Instructions,
+			Stage::LlvmIr => Self::LlvmIr,
+			Stage::Bitcode => Self::Bitcode,
+			Stage::Obj => Self::Object,
+			Stage::Asm => Self::Assembly,
+			Stage::C => Self::CSource,
+			Stage::Rust => Self::RustSource,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Width {
+	#[value(name = "8")]
+	U8,
+	#[value(name = "16")]
+	U16,
+	#[value(name = "32")]
+	U32,
+}
+
+impl From<Width> for CellWidth {
+	fn from(value: Width) -> Self {
+		match value {
+			Width::U8 => Self::U8,
+			Width::U16 => Self::U16,
+			Width::U32 => Self::U32,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Overflow {
+	Wrap,
+	Trap,
+}
+
+impl From<Overflow> for CellOverflow {
+	fn from(value: Overflow) -> Self {
+		match value {
+			Overflow::Wrap => Self::Wrap,
+			Overflow::Trap => Self::Trap,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Boundary {
+	Wrap,
+	Abort,
+	Grow,
+}
+
+impl From<Boundary> for TapeBoundary {
+	fn from(value: Boundary) -> Self {
+		match value {
+			Boundary::Wrap => Self::Wrap,
+			Boundary::Abort => Self::Abort,
+			Boundary::Grow => Self::Grow,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Eof {
+	/// Store 0.
+	Zero,
+	/// Store the largest value a cell can hold.
+	Max,
+	/// Store 10.
+	Newline,
+	/// Leave the cell as it was.
+	Unchanged,
+}
+
+impl From<Eof> for EofMode {
+	fn from(value: Eof) -> Self {
+		match value {
+			Eof::Zero => Self::Zero,
+			Eof::Max => Self::Max,
+			Eof::Newline => Self::Newline,
+			Eof::Unchanged => Self::Unchanged,
+		}
+	}
+}
+
+fn parse_tape_size(s: &str) -> Result<usize, String> {
+	let tape_size = s.parse::<usize>().map_err(|e| e.to_string())?;
+
+	if tape_size.is_power_of_two() {
+		Ok(tape_size)
+	} else {
+		Err(format!("{tape_size} is not a power of two"))
+	}
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+	let seconds = s.parse::<f64>().map_err(|e| e.to_string())?;
+
+	Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
 }
@@ -1,11 +1,22 @@
 mod args;
+mod source;
 
-use std::{fs, path::Path};
+use std::{fs, io, path::Path};
+#[cfg(all(unix, feature = "llvm"))]
+use std::{io::prelude::*, process};
 
 use clap::Parser as _;
-use color_eyre::Result;
-use frick_assembler::Assembler;
-use frick_optimizer::Optimizer;
+use color_eyre::{Result, eyre::ensure};
+#[cfg(all(unix, feature = "llvm"))]
+use frick_assembler::Sandbox;
+#[cfg(feature = "llvm")]
+use frick_assembler::{Assembler, Linker};
+use frick_instructions::BrainInstruction;
+use frick_interpreter::Interpreter;
+use frick_operations::{BrainOperation, InitialTape};
+use frick_optimizer::{Diagnostic, Optimizer};
+use frick_serialize::{Artifact, Artifacts, EmitOptions};
+use frick_spec::Spec;
 use tracing_error::ErrorLayer;
 use tracing_indicatif::{IndicatifLayer, filter::IndicatifFilter, style::ProgressStyle};
 use tracing_subscriber::{
@@ -14,7 +25,12 @@ use tracing_subscriber::{
 	prelude::*,
 };
 
-use self::args::Args;
+#[cfg(feature = "llvm")]
+use self::args::{BuildArgs, CompileArgs};
+use self::{
+	args::{Args, Backend, CheckArgs, Command, EmitArgs, RunArgs, SpecArgs},
+	source::Source,
+};
 
 fn main() -> Result<()> {
 	let args = match Args::try_parse() {
@@ -27,33 +43,67 @@ fn main() -> Result<()> {
 	install_tracing(args.output_path());
 	color_eyre::install()?;
 
-	let operations = frick_operations::parse(args.file_path())?;
+	match args.command() {
+		Command::Run(args) => run(args),
+		#[cfg(feature = "llvm")]
+		Command::Build(args) => build(args),
+		Command::Emit(args) => emit(args),
+		Command::Check(args) => check(args),
+	}
+}
 
-	if operations.is_empty() {
-		tracing::warn!("no program parsed");
+fn run(args: &RunArgs) -> Result<()> {
+	let source = args.compile.source.read()?;
+	let spec = args.compile.spec.spec();
+	let initial_tape = initial_tape(&args.compile.spec, spec)?;
 
+	let Some(operations) = parse(&source, &initial_tape)? else {
 		return Ok(());
+	};
+
+	let emit = args.emit_options();
+	let deny = args.compile.spec.deny_infinite_loops;
+
+	#[cfg(all(unix, feature = "llvm"))]
+	ensure!(
+		!args.sandbox.sandbox || matches!(args.backend, Backend::Llvm),
+		"`--sandbox` is only supported by the llvm backend"
+	);
+
+	match args.backend {
+		Backend::Interp => interpret(&source, operations, spec, emit, deny),
+		Backend::Cranelift => cranelift(&source, operations, spec, emit, deny),
+		#[cfg(feature = "llvm")]
+		Backend::Llvm => jit(args, &source, operations, spec, emit),
 	}
+}
 
-	let output = Optimizer::run(operations, args.output_path())?;
-
-	let assembler = match args.passes_path() {
-		None => Assembler::new("default<O0>".to_owned(), args.file_path().to_owned()),
-		Some(passes_path) => {
-			let passes = fs::read_to_string(passes_path)?;
-
-			Assembler::new(
-				passes
-					.lines()
-					.map(|l| l.trim())
-					.collect::<Vec<_>>()
-					.join(","),
-				args.file_path().to_owned(),
-			)
-		}
-	};
+#[cfg(feature = "llvm")]
+fn jit(
+	args: &RunArgs,
+	source: &Source,
+	operations: Vec<BrainOperation>,
+	spec: Spec,
+	emit: EmitOptions<'_>,
+) -> Result<()> {
+	let output = optimize(
+		source,
+		operations,
+		spec,
+		emit,
+		args.compile.spec.deny_infinite_loops,
+	)?;
+
+	let assembler = create_assembler(&args.compile, source, spec)?;
+
+	#[cfg(unix)]
+	if let Some(sandbox) = args.sandbox.sandbox() {
+		let linker = linker(args.sandbox.runtime_path(), None)?;
 
-	let module = assembler.assemble(&output, args.output_path())?;
+		return run_sandboxed(&assembler, &output, emit, &linker, &sandbox);
+	}
+
+	let module = assembler.assemble(&output, emit)?;
 
 	tracing::info!("finished assembling module");
 
@@ -62,17 +112,357 @@ fn main() -> Result<()> {
 	Ok(())
 }
 
-fn install_tracing(folder_path: &Path) {
-	_ = fs::remove_dir_all(folder_path);
+fn interpret(
+	source: &Source,
+	operations: Vec<BrainOperation>,
+	spec: Spec,
+	emit: EmitOptions<'_>,
+	deny: bool,
+) -> Result<()> {
+	let operations = optimize_operations(source, operations, spec, deny)?;
+
+	frick_serialize::serialize(&operations, emit, Artifact::Operations, "optimized.ops")?;
+
+	let interpreter = Interpreter::new(&operations, spec)?;
+
+	tracing::info!("finished compiling bytecode");
+
+	interpreter.run(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+
+	Ok(())
+}
+
+fn cranelift(
+	source: &Source,
+	operations: Vec<BrainOperation>,
+	spec: Spec,
+	emit: EmitOptions<'_>,
+	deny: bool,
+) -> Result<()> {
+	let output = optimize(source, operations, spec, emit, deny)?;
+
+	let module =
+		frick_cranelift::Assembler::new(source.path().to_owned(), source.text().to_owned())
+			.with_spec(spec)
+			.assemble(&output)?;
+
+	tracing::info!("finished compiling module");
+
+	module.execute()?;
+
+	Ok(())
+}
+
+#[cfg(all(unix, feature = "llvm"))]
+fn run_sandboxed(
+	assembler: &Assembler,
+	instructions: &[BrainInstruction],
+	emit: EmitOptions<'_>,
+	linker: &Linker,
+	sandbox: &Sandbox,
+) -> Result<()> {
+	let mut input = Vec::new();
+	io::stdin().read_to_end(&mut input)?;
+
+	let output = assembler.run_sandboxed(instructions, emit, linker, sandbox, &input)?;
+
+	io::stdout().write_all(output.stdout())?;
+	io::stderr().write_all(output.stderr())?;
+
+	if output.output_truncated() {
+		tracing::warn!("program was killed for going over the output limit");
+	}
+
+	if let Some(signal) = output.signal() {
+		tracing::error!(signal, "program was killed by a signal");
+	}
+
+	tracing::info!(status = %output.status(), "sandboxed program finished");
+
+	if !output.success() {
+		process::exit(output.code().unwrap_or(1));
+	}
+
+	Ok(())
+}
+
+#[cfg(feature = "llvm")]
+fn build(args: &BuildArgs) -> Result<()> {
+	let source = args.compile.source.read()?;
+	let spec = args.compile.spec.spec();
+	let initial_tape = initial_tape(&args.compile.spec, spec)?;
+
+	let Some(operations) = parse(&source, &initial_tape)? else {
+		return Ok(());
+	};
+
+	let emit = args.emit_options();
+
+	// a library function runs on whatever tape the caller hands it, and the caller reads it back
+	let spec = if args.library().is_some() {
+		spec.with_strict_semantics(true)
+	} else {
+		spec
+	};
+
+	let output = optimize(
+		&source,
+		operations,
+		spec,
+		emit,
+		args.compile.spec.deny_infinite_loops,
+	)?;
+
+	let assembler = create_assembler(&args.compile, &source, spec)?;
+
+	if let Some(name) = args.library() {
+		assembler.build_library(&output, emit, name, args.build_path())?;
+
+		tracing::info!("finished building library");
+
+		return Ok(());
+	}
+
+	let linker = linker(args.runtime_path(), args.linker())?;
+
+	assembler.build(&output, emit, &linker, args.build_path())?;
+
+	tracing::info!("finished building executable");
+
+	Ok(())
+}
+
+fn emit(args: &EmitArgs) -> Result<()> {
+	let source = args.compile.source.read()?;
+	let spec = args.compile.spec.spec();
+	let initial_tape = initial_tape(&args.compile.spec, spec)?;
+
+	let Some(operations) = parse(&source, &initial_tape)? else {
+		return Ok(());
+	};
+
+	let output_path = args.compile.output_path().unwrap_or_else(|| Path::new("."));
 
-	fs::create_dir_all(folder_path).unwrap();
+	let artifact = Artifact::from(args.stage);
+	let emit = EmitOptions::new(output_path, Artifacts::from(artifact));
 
-	let log_file = fs::OpenOptions::new()
-		.create(true)
-		.write(true)
-		.truncate(true)
-		.open(folder_path.join("output.log"))
-		.expect("failed to create log file");
+	let operations = optimize_operations(
+		&source,
+		operations,
+		spec,
+		args.compile.spec.deny_infinite_loops,
+	)?;
+
+	if matches!(artifact, Artifact::Operations) {
+		frick_serialize::serialize(&operations, emit, artifact, "optimized.ops")?;
+
+		return Ok(());
+	}
+
+	if matches!(artifact, Artifact::CSource | Artifact::RustSource) {
+		let source = match artifact {
+			Artifact::CSource => frick_transpiler::emit_c(&operations, spec)?,
+			_ => frick_transpiler::emit_rust(&operations, spec)?,
+		};
+
+		fs::write(
+			output_path.join(format!("optimized.{}", artifact.extension())),
+			source,
+		)?;
+
+		tracing::info!(%artifact, "finished emitting");
+
+		return Ok(());
+	}
+
+	#[cfg(not(feature = "llvm"))]
+	if !matches!(artifact, Artifact::Instructions) {
+		color_eyre::eyre::bail!("emitting {artifact} requires the `llvm` feature");
+	}
+
+	let instructions = optimize_instructions(&operations, spec)?;
+
+	if matches!(artifact, Artifact::Instructions) {
+		frick_serialize::serialize(&instructions, emit, artifact, "optimized.instrs")?;
+
+		return Ok(());
+	}
+
+	#[cfg(feature = "llvm")]
+	{
+		let assembler = create_assembler(&args.compile, &source, spec)?;
+
+		assembler.emit(&instructions, output_path, artifact)?;
+
+		tracing::info!(%artifact, "finished emitting");
+	}
+
+	Ok(())
+}
+
+fn check(args: &CheckArgs) -> Result<()> {
+	let source = args.source.read()?;
+	let spec = args.spec.spec();
+	let initial_tape = initial_tape(&args.spec, spec)?;
+
+	let Some(operations) = parse(&source, &initial_tape)? else {
+		return Ok(());
+	};
+
+	let operations = optimize_operations(&source, operations, spec, args.spec.deny_infinite_loops)?;
+
+	optimize_instructions(&operations, spec)?;
+
+	tracing::info!("program is valid");
+
+	Ok(())
+}
+
+fn parse(source: &Source, initial_tape: &InitialTape) -> Result<Option<Vec<BrainOperation>>> {
+	let mut operations = match frick_operations::parse_bytes(source.bytes()) {
+		Ok(operations) => operations,
+		Err(e) => {
+			e.report(&source.name(), source.text())?;
+
+			return Err(e.into());
+		}
+	};
+
+	if operations.is_empty() {
+		tracing::warn!("no program parsed");
+
+		return Ok(None);
+	}
+
+	operations.splice(0..0, initial_tape.prologue());
+
+	Ok(Some(operations))
+}
+
+fn initial_tape(args: &SpecArgs, spec: Spec) -> Result<InitialTape> {
+	let initial_tape = args.initial_tape()?;
+
+	ensure!(
+		initial_tape.min_tape_size() <= spec.tape_size(),
+		"the initial tape needs {} cells, but the tape only holds {}",
+		initial_tape.min_tape_size(),
+		spec.tape_size()
+	);
+
+	let cell_width = spec.cell_width();
+
+	ensure!(
+		initial_tape
+			.cells()
+			.iter()
+			.all(|&cell| cell <= cell_width.max()),
+		"initial cells must fit in {} bits",
+		cell_width.bits()
+	);
+
+	Ok(initial_tape)
+}
+
+fn optimize(
+	source: &Source,
+	operations: Vec<BrainOperation>,
+	spec: Spec,
+	emit: EmitOptions<'_>,
+	deny: bool,
+) -> Result<Vec<BrainInstruction>> {
+	let mut diagnostics = Vec::new();
+
+	let output = Optimizer::run(operations, spec, emit, &mut diagnostics);
+
+	report(source, &diagnostics, deny)?;
+
+	Ok(output?)
+}
+
+fn optimize_operations(
+	source: &Source,
+	operations: Vec<BrainOperation>,
+	spec: Spec,
+	deny: bool,
+) -> Result<Vec<BrainOperation>> {
+	let mut diagnostics = Vec::new();
+
+	let operations = Optimizer::optimize_operations(operations, spec, &mut diagnostics);
+
+	report(source, &diagnostics, deny)?;
+
+	Ok(operations)
+}
+
+fn report(source: &Source, diagnostics: &[Diagnostic], deny: bool) -> Result<()> {
+	for diagnostic in diagnostics {
+		diagnostic.report(&source.name(), source.text(), deny)?;
+	}
+
+	ensure!(
+		!deny || diagnostics.is_empty(),
+		"{} diagnostic(s) were promoted to errors",
+		diagnostics.len()
+	);
+
+	Ok(())
+}
+
+fn optimize_instructions(
+	operations: &[BrainOperation],
+	spec: Spec,
+) -> Result<Vec<BrainInstruction>> {
+	let instructions = Optimizer::lower_operations(operations, spec);
+
+	Ok(Optimizer::optimize_instructions(instructions, spec)?)
+}
+
+#[cfg(feature = "llvm")]
+fn linker(runtime_path: Option<&Path>, program: Option<&Path>) -> Result<Linker> {
+	let linker = match runtime_path {
+		Some(runtime_path) => Linker::new(runtime_path.to_owned()),
+		None => Linker::from_current_exe()?,
+	};
+
+	Ok(match program {
+		Some(program) => linker.with_program(program),
+		None => linker,
+	})
+}
+
+#[cfg(feature = "llvm")]
+fn create_assembler(args: &CompileArgs, source: &Source, spec: Spec) -> Result<Assembler> {
+	let passes = match args.passes_path() {
+		None => "default<O0>".to_owned(),
+		Some(passes_path) => fs::read_to_string(passes_path)?
+			.lines()
+			.map(|l| l.trim())
+			.collect::<Vec<_>>()
+			.join(","),
+	};
+
+	Ok(Assembler::new(passes, source.path().to_owned(), source.text().to_owned()).with_spec(spec))
+}
+
+fn install_tracing(folder_path: Option<&Path>) {
+	let file_layer = folder_path.map(|folder_path| {
+		_ = fs::remove_dir_all(folder_path);
+
+		fs::create_dir_all(folder_path).unwrap();
+
+		let log_file = fs::OpenOptions::new()
+			.create(true)
+			.write(true)
+			.truncate(true)
+			.open(folder_path.join("output.log"))
+			.expect("failed to create log file");
+
+		fmt::layer()
+			.with_target(false)
+			.with_ansi(false)
+			.with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+			.with_writer(log_file)
+	});
 
 	let indicatif_layer = IndicatifLayer::new().with_progress_style(
 		ProgressStyle::with_template(
@@ -82,12 +472,6 @@ fn install_tracing(folder_path: &Path) {
 		.progress_chars("#>-"),
 	);
 
-	let file_layer = fmt::layer()
-		.with_target(false)
-		.with_ansi(false)
-		.with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-		.with_writer(log_file);
-
 	let fmt_layer = fmt::layer()
 		.with_target(false)
 		.with_writer(indicatif_layer.get_stderr_writer())
@@ -0,0 +1,62 @@
+use std::{
+	fs,
+	io::{self, Read as _},
+	path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone)]
+pub struct Source {
+	path: PathBuf,
+	bytes: Vec<u8>,
+	text: String,
+}
+
+impl Source {
+	pub fn read(file_path: Option<&Path>, expression: Option<&str>) -> io::Result<Self> {
+		if let Some(expression) = expression {
+			return Ok(Self::new(
+				PathBuf::from("<expression>"),
+				expression.as_bytes().to_owned(),
+			));
+		}
+
+		if let Some(file_path) = file_path {
+			return Ok(Self::new(file_path.to_owned(), fs::read(file_path)?));
+		}
+
+		let mut bytes = Vec::new();
+
+		io::stdin().lock().read_to_end(&mut bytes)?;
+
+		Ok(Self::new(PathBuf::from("<stdin>"), bytes))
+	}
+
+	// Any byte that isn't a command is a comment, so the source doesn't have to be UTF-8, invalid
+	// bytes only show up as a single `?` each in `text` to keep byte offsets lined up with `bytes`
+	fn new(path: PathBuf, bytes: Vec<u8>) -> Self {
+		let mut text = String::with_capacity(bytes.len());
+
+		for chunk in bytes.utf8_chunks() {
+			text.push_str(chunk.valid());
+			text.extend(chunk.invalid().iter().map(|_| '?'));
+		}
+
+		Self { path, bytes, text }
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn name(&self) -> String {
+		self.path.display().to_string()
+	}
+
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+}
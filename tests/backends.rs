@@ -0,0 +1,219 @@
+#![cfg(feature = "llvm")]
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use frick_assembler::{Assembler, AssemblyError, StopReason, TapeSnapshot};
+use frick_interpreter::{Interpreter, InterpreterError};
+use frick_optimizer::Optimizer;
+use frick_serialize::EmitOptions;
+use frick_spec::{CellWidth, EofMode, Spec, TapeBoundary};
+
+/// Each example with its input, along with the EOF it was written for.
+const EXAMPLES: &[(&str, &[u8], EofMode)] = &[
+	("666.bf", b"", EofMode::Newline),
+	("a_to_z.bf", b"", EofMode::Newline),
+	("ascii.bf", b"", EofMode::Newline),
+	("bitwise.bf", b"", EofMode::Newline),
+	("bottles.bf", b"", EofMode::Newline),
+	("chars.bf", b"", EofMode::Newline),
+	("collatz.bf", b"27\n97\n", EofMode::Zero),
+	("diamond.bf", b"", EofMode::Newline),
+	("echo.bf", b"x", EofMode::Newline),
+	(
+		"head.bf",
+		b"1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n",
+		EofMode::Newline,
+	),
+	("hello_world.bf", b"", EofMode::Newline),
+	("rot13.bf", b"Hello, World!\n", EofMode::Zero),
+	("serptri.bf", b"", EofMode::Newline),
+	("squares.bf", b"", EofMode::Newline),
+];
+
+fn interpret(
+	program: &[u8],
+	spec: Spec,
+	input: &[u8],
+) -> (Vec<u8>, Result<TapeSnapshot, InterpreterError>) {
+	let ops = Optimizer::optimize_operations(
+		frick_operations::parse_bytes(program).unwrap(),
+		spec,
+		&mut Vec::new(),
+	);
+
+	let mut output = Vec::new();
+	let result = Interpreter::new(&ops, spec)
+		.and_then(|interpreter| interpreter.run(&mut &*input, &mut output));
+
+	(output, result)
+}
+
+/// The tape is only returned for programs compiled with strict semantics.
+fn llvm(
+	program: &[u8],
+	spec: Spec,
+	input: &[u8],
+) -> (Vec<u8>, Result<Option<TapeSnapshot>, AssemblyError>) {
+	let ops = frick_operations::parse_bytes(program).unwrap();
+	let instrs = Optimizer::run(ops, spec, EmitOptions::none(), &mut Vec::new()).unwrap();
+
+	let assembler = Assembler::new(
+		"default<O0>".to_owned(),
+		PathBuf::from("test.bf"),
+		String::from_utf8_lossy(program).into_owned(),
+	)
+	.with_spec(spec);
+
+	let mut output = Vec::new();
+	let result = match assembler.assemble(&instrs, EmitOptions::none()) {
+		Ok(module) if spec.strict_semantics() => module
+			.execute_snapshot_with(&mut &*input, &mut output)
+			.map(Some),
+		Ok(module) => module
+			.execute_with(&mut &*input, &mut output)
+			.map(|()| None),
+		Err(e) => Err(e),
+	};
+
+	(output, result)
+}
+
+/// Backends grow the tape by different amounts, so only the cells up to the last non-zero one
+/// or the pointer are compared.
+fn used_cells(snapshot: &TapeSnapshot) -> &[u32] {
+	let used = snapshot
+		.tape()
+		.iter()
+		.rposition(|&cell| !matches!(cell, 0))
+		.map_or(0, |last| last + 1)
+		.max(snapshot.pointer() + 1);
+
+	&snapshot.tape()[..used]
+}
+
+fn compare_output(name: &str, program: &[u8], spec: Spec, input: &[u8]) {
+	let (expected, llvm_result) = llvm(program, spec, input);
+	let (output, result) = interpret(program, spec, input);
+
+	llvm_result.unwrap();
+	result.unwrap();
+
+	assert_eq!(
+		String::from_utf8_lossy(&output),
+		String::from_utf8_lossy(&expected),
+		"{name} with {spec:?}"
+	);
+}
+
+fn compare_tape(program: &str, spec: Spec, input: &[u8]) {
+	let spec = spec.with_strict_semantics(true);
+
+	let (expected_output, expected) = llvm(program.as_bytes(), spec, input);
+	let (output, snapshot) = interpret(program.as_bytes(), spec, input);
+
+	let expected = expected.unwrap().unwrap();
+	let snapshot = snapshot.unwrap();
+
+	assert_eq!(output, expected_output, "{program} with {spec:?}");
+	assert_eq!(
+		snapshot.pointer(),
+		expected.pointer(),
+		"{program} with {spec:?}"
+	);
+	assert_eq!(
+		used_cells(&snapshot),
+		used_cells(&expected),
+		"{program} with {spec:?}"
+	);
+}
+
+#[test]
+fn examples_match_llvm() {
+	let programs = Path::new(env!("CARGO_MANIFEST_DIR")).join("programs");
+
+	for &(name, input, eof) in EXAMPLES {
+		let program = fs::read(programs.join(name)).unwrap();
+		let spec = Spec::new().with_eof(eof);
+
+		compare_output(name, &program, spec, input);
+		compare_output(name, &program, spec.with_strict_semantics(true), input);
+	}
+}
+
+#[test]
+fn tapes_match_llvm() {
+	compare_tape("<+<++", Spec::new().with_boundary(TapeBoundary::Wrap), b"");
+	compare_tape(">>+<-", Spec::new().with_boundary(TapeBoundary::Abort), b"");
+	compare_tape(
+		&format!("{}+>++", ">".repeat(40)),
+		Spec::new()
+			.with_boundary(TapeBoundary::Grow)
+			.with_tape_size(16)
+			.unwrap(),
+		b"",
+	);
+
+	for width in [CellWidth::U8, CellWidth::U16, CellWidth::U32] {
+		for eof in [
+			EofMode::Zero,
+			EofMode::Max,
+			EofMode::Newline,
+			EofMode::Unchanged,
+		] {
+			compare_tape(
+				"+++++++,.",
+				Spec::new().with_cell_width(width).with_eof(eof),
+				b"",
+			);
+		}
+
+		let spec = Spec::new().with_cell_width(width);
+
+		compare_tape(",>,", spec, b"a");
+		compare_tape("->+++[-<+>]<.", spec, b"");
+		compare_tape("-[->+<]>+", spec, b"");
+	}
+}
+
+#[test]
+fn step_limit_matches_llvm() {
+	let spec = Spec::new()
+		.with_strict_semantics(true)
+		.with_step_limit(1000);
+
+	let (_, expected) = llvm(b"+[>+<]", spec, b"");
+	let (_, result) = interpret(b"+[>+<]", spec, b"");
+
+	assert!(matches!(
+		expected,
+		Err(AssemblyError::Stopped(StopReason::StepLimit(1000)))
+	));
+	assert!(matches!(
+		result,
+		Err(InterpreterError::Stopped(StopReason::StepLimit(1000)))
+	));
+}
+
+#[test]
+fn timeout_matches_llvm() {
+	let timeout = Duration::from_millis(20);
+	let spec = Spec::new()
+		.with_strict_semantics(true)
+		.with_timeout(timeout);
+
+	let (_, expected) = llvm(b"+[>+<]", spec, b"");
+	let (_, result) = interpret(b"+[>+<]", spec, b"");
+
+	assert!(matches!(
+		expected,
+		Err(AssemblyError::Stopped(StopReason::Timeout(t))) if t == timeout
+	));
+	assert!(matches!(
+		result,
+		Err(InterpreterError::Stopped(StopReason::Timeout(t))) if t == timeout
+	));
+}